@@ -1,7 +1,109 @@
-use std::collections::HashMap;
-
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote, TokenStreamExt};
-use syn::{Data, DeriveInput, Field, Type};
+use syn::{parse_quote, Data, DeriveInput, Field, GenericArgument, PathArguments, Type};
+
+/// How a `#[deserialize_struct(..)]` field's *real* type wraps the nested config type, detected
+/// from the field's own declared type (`Option<T>`/`Vec<T>`/`HashMap<K, T>`) rather than from the
+/// attribute, which only ever names the nested `De<T>` type.
+enum Wrapper {
+    Scalar,
+    Option(Type),
+    Vec(Type),
+    Map(Type, Type),
+}
+
+/// Recognizes `Option<T>`, `Vec<T>` and `HashMap<K, V>` by their last path segment; anything else
+/// (including the common case of a bare nested config type) is treated as `Wrapper::Scalar`.
+fn classify_wrapper(ty: &Type) -> Wrapper {
+    let Type::Path(type_path) = ty else {
+        return Wrapper::Scalar;
+    };
+    let Some(segment) = type_path.path.segments.last() else {
+        return Wrapper::Scalar;
+    };
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Wrapper::Scalar;
+    };
+    let type_args: Vec<&Type> = args
+        .args
+        .iter()
+        .filter_map(|arg| match arg {
+            GenericArgument::Type(t) => Some(t),
+            _ => None,
+        })
+        .collect();
+    match (segment.ident.to_string().as_str(), type_args.as_slice()) {
+        ("Option", [inner]) => Wrapper::Option((*inner).clone()),
+        ("Vec", [inner]) => Wrapper::Vec((*inner).clone()),
+        ("HashMap", [key, value]) => Wrapper::Map((*key).clone(), (*value).clone()),
+        _ => Wrapper::Scalar,
+    }
+}
+
+/// The type stored inside the generated `Option<..>` field of `De<Name>`, given the nested `De<T>`
+/// type named by `#[deserialize_struct(..)]` and the wrapper detected around the real field type.
+fn wrapped_de_type(de_ty: &Type, wrapper: &Wrapper) -> Type {
+    match wrapper {
+        Wrapper::Scalar => de_ty.clone(),
+        Wrapper::Option(_) => parse_quote!(::std::option::Option<#de_ty>),
+        Wrapper::Vec(_) => parse_quote!(::std::vec::Vec<#de_ty>),
+        Wrapper::Map(key, _) => parse_quote!(::std::collections::HashMap<#key, #de_ty>),
+    }
+}
+
+/// Builds the expression that merges a nested field's optional override (`self_expr`, typed per
+/// [`wrapped_de_type`]) on top of its resolved default (`default_expr`, a place of the real nested
+/// type or collection of it), delegating to the nested type's own `into_config` element-wise.
+fn nested_merge_expr(
+    wrapper: &Wrapper,
+    self_expr: &TokenStream,
+    default_expr: &TokenStream,
+) -> TokenStream {
+    match wrapper {
+        Wrapper::Scalar => quote! {
+            match #self_expr {
+                ::std::option::Option::Some(val) => val.into_config(&#default_expr),
+                ::std::option::Option::None => #default_expr.clone(),
+            }
+        },
+        Wrapper::Option(inner_ty) => quote! {
+            match #self_expr {
+                ::std::option::Option::Some(::std::option::Option::Some(val)) => {
+                    let base = (#default_expr).clone().unwrap_or_else(#inner_ty::default);
+                    ::std::option::Option::Some(val.into_config(&base))
+                }
+                ::std::option::Option::Some(::std::option::Option::None) => ::std::option::Option::None,
+                ::std::option::Option::None => (#default_expr).clone(),
+            }
+        },
+        Wrapper::Vec(inner_ty) => quote! {
+            match #self_expr {
+                ::std::option::Option::Some(vals) => vals
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, val)| {
+                        let base = (#default_expr).get(i).cloned().unwrap_or_else(#inner_ty::default);
+                        val.into_config(&base)
+                    })
+                    .collect::<::std::vec::Vec<_>>(),
+                ::std::option::Option::None => (#default_expr).clone(),
+            }
+        },
+        Wrapper::Map(_key_ty, inner_ty) => quote! {
+            match #self_expr {
+                ::std::option::Option::Some(map) => {
+                    let mut merged = (#default_expr).clone();
+                    for (k, val) in map {
+                        let base = merged.get(&k).cloned().unwrap_or_else(#inner_ty::default);
+                        merged.insert(k, val.into_config(&base));
+                    }
+                    merged
+                }
+                ::std::option::Option::None => (#default_expr).clone(),
+            }
+        },
+    }
+}
 
 pub fn derive_multi_widget_config_main(input: DeriveInput) -> proc_macro2::TokenStream {
     let DeriveInput {
@@ -97,74 +199,223 @@ pub fn derive_multi_widget_config_main(input: DeriveInput) -> proc_macro2::Token
     config_main_struct
 }
 
-pub fn derive_config_de(input: DeriveInput) -> proc_macro2::TokenStream {
-    let DeriveInput {
-        ident, data, vis, ..
-    } = input;
-    let ident_de = format_ident!("De{}", &ident);
-    let mut opt = HashMap::new();
-    let mut opt_de_struct = HashMap::new();
+/// One field of a `#[derive(OptDeserializeConfig)]` struct, in declaration order.
+struct OptField {
+    ident: syn::Ident,
+    /// The type stored inside the generated `Option<..>` (the `#[deserialize_struct(..)]`
+    /// argument, itself re-wrapped in `Option`/`Vec`/`HashMap` to match the real field, when
+    /// present; otherwise the field's own type).
+    ty: Type,
+    attrs: Vec<syn::Attribute>,
+    is_nested: bool,
+    /// How the real field wraps the nested config named by `#[deserialize_struct(..)]`; only
+    /// meaningful when `is_nested` is true.
+    wrapper: Wrapper,
+    /// Marked `#[config(flatten)]`: this nested field's keys are read from the parent object
+    /// directly instead of from a nested `{ "field": { .. } }` object.
+    is_flatten: bool,
+}
+
+fn collect_opt_fields(data: &Data) -> Vec<OptField> {
+    let mut fields = Vec::new();
     if let Data::Struct(ds) = data {
         for field in ds.fields.iter() {
             let deserialize_attr = field
                 .attrs
                 .iter()
                 .find(|attr| attr.path().is_ident("deserialize_struct"));
+            let is_flatten = field.attrs.iter().any(|attr| {
+                attr.path().is_ident("config")
+                    && attr
+                        .parse_nested_meta(|meta| {
+                            if meta.path.is_ident("flatten") {
+                                Ok(())
+                            } else {
+                                Err(meta.error("unrecognized #[config(..)] attribute"))
+                            }
+                        })
+                        .is_ok()
+            });
             let Field {
-                attrs,
-                vis: _,
-                mutability: _,
-                ident,
-                colon_token,
-                ty,
+                attrs, ident, ty, ..
             } = field;
-            let ty = match deserialize_attr {
+            let wrapper = classify_wrapper(ty);
+            let field_ty = match deserialize_attr {
                 Some(attr) => {
-                    let ty: Type = attr
+                    let de_ty: Type = attr
                         .parse_args()
                         .expect("error parsing deserialize_struct attribute");
-                    ty
+                    wrapped_de_type(&de_ty, &wrapper)
                 }
                 None => ty.clone(),
             };
-            let attrs = attrs.iter().filter(|attr| {
-                !(attr.path().is_ident("deserialize_struct") || attr.path().is_ident("child_only"))
-            });
-            let field = quote! {
-                #(#attrs)*
-                pub(crate) #ident #colon_token ::std::option::Option<#ty>
-            };
-            if deserialize_attr.is_some() {
-                opt_de_struct.insert(ident.clone().unwrap(), field);
-            } else {
-                opt.insert(ident.clone().unwrap(), field);
+            if is_flatten && deserialize_attr.is_none() {
+                panic!("#[config(flatten)] requires #[deserialize_struct(..)] on the same field");
+            }
+            if is_flatten && !matches!(wrapper, Wrapper::Scalar) {
+                panic!(
+                    "#[config(flatten)] does not support Option/Vec/HashMap-wrapped nested configs"
+                );
             }
+            // `De<Name>` no longer derives any serde trait (it has a hand-written `Deserialize`
+            // impl below), so `#[serde(..)]` attributes from the original field would be dead
+            // weight at best and a "no such attribute" compile error at worst
+            let attrs = attrs
+                .iter()
+                .filter(|attr| {
+                    !(attr.path().is_ident("deserialize_struct")
+                        || attr.path().is_ident("child_only")
+                        || attr.path().is_ident("config")
+                        || attr.path().is_ident("serde"))
+                })
+                .cloned()
+                .collect();
+            fields.push(OptField {
+                ident: ident.clone().unwrap(),
+                ty: field_ty,
+                attrs,
+                is_nested: deserialize_attr.is_some(),
+                wrapper,
+                is_flatten,
+            });
         }
     }
-    let opt_fields = opt.values();
-    let opt_de_struct_fields = opt_de_struct.values();
+    let flatten_count = fields.iter().filter(|f| f.is_flatten).count();
+    if flatten_count > 1 {
+        panic!("OptDeserializeConfig only supports one #[config(flatten)] field per struct");
+    }
+    fields
+}
+
+pub fn derive_config_de(input: DeriveInput) -> proc_macro2::TokenStream {
+    let DeriveInput {
+        ident, data, vis, ..
+    } = input;
+    let ident_de = format_ident!("De{}", &ident);
+    let fields = collect_opt_fields(&data);
+
+    let struct_fields = fields.iter().map(|f| {
+        let OptField {
+            ident, ty, attrs, ..
+        } = f;
+        quote! {
+            #(#attrs)*
+            pub(crate) #ident: ::std::option::Option<#ty>
+        }
+    });
+
+    let flatten_field = fields.iter().find(|f| f.is_flatten);
+
+    // reserved key, recognized on every `De<Name>`, naming a preset this entry should be merged
+    // on top of (base -> preset -> this entry) instead of directly on top of the base config
+    let preset_arm = quote! {
+        "preset" => {
+            match ::serde_json::from_value::<::std::string::String>(value) {
+                ::std::result::Result::Ok(v) => this.preset = ::std::option::Option::Some(v),
+                ::std::result::Result::Err(err) => {
+                    ::dynisland_core::abi::log::warn!(
+                        "ignoring invalid value for reserved config key `preset`: {}",
+                        err
+                    );
+                }
+            }
+        }
+    };
+
+    // a hand-written map visitor instead of `#[derive(serde::Deserialize)]` so a single bad or
+    // unknown field only loses that one field (logged) instead of the whole config
+    let match_arms = fields.iter().filter(|f| !f.is_flatten).map(|f| {
+        let OptField { ident, ty, .. } = f;
+        let key = ident.to_string();
+        quote! {
+            #key => {
+                let keep_default = matches!(
+                    &value,
+                    ::serde_json::Value::String(s) if s == "none" || s == "default"
+                );
+                if !keep_default {
+                    match ::serde_json::from_value::<#ty>(value) {
+                        ::std::result::Result::Ok(v) => this.#ident = ::std::option::Option::Some(v),
+                        ::std::result::Result::Err(err) => {
+                            ::dynisland_core::abi::log::warn!(
+                                "ignoring invalid value for config field `{}`: {}",
+                                #key, err
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    // unrecognized keys either get logged (no flatten field to claim them) or buffered and
+    // handed to the flattened field's own tolerant `Deserialize` impl
+    let flattened_decl =
+        flatten_field.map(|_| quote! { let mut flattened = ::serde_json::Map::new(); });
+    let unknown_key_arm = match flatten_field {
+        Some(_) => quote! {
+            _ => {
+                flattened.insert(key, value);
+            }
+        },
+        None => quote! {
+            _ => {
+                ::dynisland_core::abi::log::warn!("ignoring unknown config key `{}`", key);
+            }
+        },
+    };
+    let flatten_assign = flatten_field.map(|f| {
+        let OptField { ident, ty, .. } = f;
+        quote! {
+            this.#ident = ::serde_json::from_value::<#ty>(::serde_json::Value::Object(flattened)).ok();
+        }
+    });
+
     let mut de_config_struct = quote! {
-        #[derive(Debug, Clone, Default, serde::Deserialize)]
-        #[serde(default)]
+        #[derive(Debug, Clone, Default)]
         #vis struct #ident_de{
-            #(#opt_fields,)*
-            #(#opt_de_struct_fields,)*
+            #(#struct_fields,)*
+            pub(crate) preset: ::std::option::Option<::std::string::String>,
         }
 
+        impl<'de> ::serde::Deserialize<'de> for #ident_de {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                let map: ::std::collections::HashMap<String, ::serde_json::Value> =
+                    ::serde::Deserialize::deserialize(deserializer)?;
+                let mut this = Self::default();
+                #flattened_decl
+                for (key, value) in map {
+                    match key.as_str() {
+                        #preset_arm
+                        #(#match_arms)*
+                        #unknown_key_arm
+                    }
+                }
+                #flatten_assign
+                ::std::result::Result::Ok(this)
+            }
+        }
     };
-    let opt_ident = opt.keys();
-    let opt_de_struct_ident = opt_de_struct.keys();
-    let opt_de_struct_ident1 = opt_de_struct.keys();
+
+    let plain_idents = fields.iter().filter(|f| !f.is_nested).map(|f| &f.ident);
+    let nested_idents1 = fields.iter().filter(|f| f.is_nested).map(|f| &f.ident);
+    let nested_bindings = fields.iter().filter(|f| f.is_nested).map(|f| {
+        let OptField { ident, wrapper, .. } = f;
+        let self_expr = quote! { self.#ident };
+        let default_expr = quote! { default.#ident };
+        let merge_expr = nested_merge_expr(wrapper, &self_expr, &default_expr);
+        quote! { let #ident = #merge_expr; }
+    });
     let de_config_impl = quote! {
         impl #ident_de {
             pub fn into_config(self, default: &#ident) -> #ident {
-                #(let #opt_de_struct_ident = match self.#opt_de_struct_ident{
-                    Some(val) => val.into_config(&default.#opt_de_struct_ident),
-                    None => default.#opt_de_struct_ident.clone(),
-                };)*
+                #(#nested_bindings)*
                 #ident{
-                    #(#opt_ident: self.#opt_ident.unwrap_or(default.#opt_ident.clone()),)*
-                    #(#opt_de_struct_ident1: #opt_de_struct_ident1,)*
+                    #(#plain_idents: self.#plain_idents.unwrap_or(default.#plain_idents.clone()),)*
+                    #(#nested_idents1: #nested_idents1,)*
                 }
             }
         }
@@ -185,6 +436,7 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
     let mut child_fields_ident = Vec::new();
     let mut child_fields = Vec::new();
     let mut de_struct_fields_ident = Vec::new();
+    let mut de_struct_wrappers = Vec::new();
     if let Data::Struct(ds) = data {
         for field in ds.fields.iter() {
             let child_only_attr = field
@@ -195,6 +447,18 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
                 .attrs
                 .iter()
                 .find(|attr| attr.path().is_ident("deserialize_struct"));
+            let is_flatten = field.attrs.iter().any(|attr| {
+                attr.path().is_ident("config")
+                    && attr
+                        .parse_nested_meta(|meta| {
+                            if meta.path.is_ident("flatten") {
+                                Ok(())
+                            } else {
+                                Err(meta.error("unrecognized #[config(..)] attribute"))
+                            }
+                        })
+                        .is_ok()
+            });
             let Field {
                 attrs,
                 vis: _,
@@ -204,9 +468,16 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
                 ty,
             } = field;
             let attrs = attrs.iter().filter(|attr| {
-                !attr.path().is_ident("deserialize_struct") && !attr.path().is_ident("child_only")
+                !attr.path().is_ident("deserialize_struct")
+                    && !attr.path().is_ident("child_only")
+                    && !attr.path().is_ident("config")
             });
+            // this struct keeps deriving `serde::Deserialize` directly (it's the base config, not
+            // the per-field-tolerant `De<Name>`), so a flattened nested config can just reuse
+            // serde's own `#[serde(flatten)]` support
+            let flatten_attr = is_flatten.then(|| quote! { #[serde(flatten)] });
             let field = quote! {
+                #flatten_attr
                 #(#attrs)*
                 pub(crate) #ident #colon_token #ty
             };
@@ -216,6 +487,7 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
             } else {
                 if deserialize_attr.is_some() {
                     de_struct_fields_ident.push(ident.clone().unwrap());
+                    de_struct_wrappers.push(classify_wrapper(ty));
                 } else {
                     fields_ident.push(ident.clone().unwrap());
                 }
@@ -230,7 +502,10 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
         #[serde(default)]
         #vis struct #ident_main_de {
             #(#fields,)*
-            pub(crate) windows: ::std::collections::HashMap<String, Vec<#ident_de>>
+            pub(crate) windows: ::std::collections::HashMap<String, Vec<#ident_de>>,
+            /// Named variants that inherit from the base config and can be selected per-window
+            /// with a reserved `"preset"` key, e.g. `{ "preset": "night", ... }`.
+            pub(crate) presets: ::std::collections::HashMap<String, #ident_de>
         }
         impl ::std::default::Default for #ident_main_de {
             fn default() -> Self {
@@ -240,12 +515,21 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
                     #(#fields_ident: child_default.#fields_ident,)*
                     #(#de_struct_fields_ident1: child_default.#de_struct_fields_ident,)*
                     windows: map,
+                    presets: ::std::collections::HashMap::new(),
                 }
             }
         }
     };
 
-    let de_struct_fields_ident2 = de_struct_fields_ident.clone();
+    let de_struct_merges = de_struct_fields_ident
+        .iter()
+        .zip(de_struct_wrappers.iter())
+        .map(|(ident, wrapper)| {
+            let self_expr = quote! { opt_conf.#ident };
+            let default_expr = quote! { base.#ident };
+            let merge_expr = nested_merge_expr(wrapper, &self_expr, &default_expr);
+            quote! { #ident: #merge_expr }
+        });
     let config_main_impl = quote! {
         impl #ident_main_de {
             pub fn into_main_config(self) -> #ident_main {
@@ -256,13 +540,25 @@ pub fn derive_multi_widget_config_de_main(input: DeriveInput) -> proc_macro2::To
                     windows: ::std::collections::HashMap::new(),
                 };
                 let child_default = main_conf.default_conf();
+                // resolve each preset against the base config, forming a middle layer windows
+                // can opt into with a `"preset"` key instead of repeating every override
+                let presets: ::std::collections::HashMap<::std::string::String, #ident> = self
+                    .presets
+                    .into_iter()
+                    .map(|(name, preset_conf)| (name, preset_conf.into_config(&child_default)))
+                    .collect();
                 for (name, opt_vec_conf) in self.windows {
                     let mut vec_conf = Vec::new();
                     for opt_conf in opt_vec_conf {
+                        let base = opt_conf
+                            .preset
+                            .as_deref()
+                            .and_then(|preset_name| presets.get(preset_name))
+                            .unwrap_or(&child_default);
                         let conf = #ident {
-                            #(#fields_ident: opt_conf.#fields_ident.unwrap_or(main_conf.#fields_ident.clone()),)*
-                            #(#de_struct_fields_ident2: opt_conf.#de_struct_fields_ident1.unwrap_or_default().into_config(&main_conf.#de_struct_fields_ident1),)*
-                            #(#child_fields_ident: opt_conf.#child_fields_ident.unwrap_or(child_default.#child_fields_ident.clone()),)*
+                            #(#fields_ident: opt_conf.#fields_ident.unwrap_or(base.#fields_ident.clone()),)*
+                            #(#de_struct_merges,)*
+                            #(#child_fields_ident: opt_conf.#child_fields_ident.unwrap_or(base.#child_fields_ident.clone()),)*
                         };
                         vec_conf.push(conf);
                     }
@@ -0,0 +1,80 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+/// Generates a case-insensitive, never-failing `Deserialize` impl for a unit-variant enum: any
+/// capitalization of a variant's name (or an alias declared with `#[config(alias = "...")]`)
+/// matches it, and an unrecognized value logs a warning and falls back to the variant marked
+/// `#[config(default)]` instead of failing the parse.
+pub fn derive_config_enum(input: DeriveInput) -> TokenStream {
+    let DeriveInput { ident, data, .. } = input;
+    let Data::Enum(data_enum) = data else {
+        panic!("ConfigEnum can only be derived for enums");
+    };
+
+    let mut default_variant = None;
+    // (accepted lowercase name, variant it maps to)
+    let mut accepted = Vec::new();
+    for variant in data_enum.variants.iter() {
+        if !matches!(variant.fields, Fields::Unit) {
+            panic!("ConfigEnum only supports unit variants");
+        }
+        let variant_ident = &variant.ident;
+        let mut names = vec![variant_ident.to_string().to_lowercase()];
+        let mut is_default = false;
+        for attr in &variant.attrs {
+            if !attr.path().is_ident("config") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    is_default = true;
+                    Ok(())
+                } else if meta.path.is_ident("alias") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    names.push(lit.value().to_lowercase());
+                    Ok(())
+                } else {
+                    Err(meta.error("unrecognized #[config(..)] attribute"))
+                }
+            })
+            .expect("error parsing #[config(..)] attribute");
+        }
+        if is_default {
+            if default_variant.is_some() {
+                panic!("ConfigEnum only supports one #[config(default)] variant");
+            }
+            default_variant = Some(variant_ident.clone());
+        }
+        for name in names {
+            accepted.push((name, variant_ident.clone()));
+        }
+    }
+    let default_variant =
+        default_variant.expect("ConfigEnum requires a variant marked #[config(default)]");
+
+    let match_names = accepted.iter().map(|(name, _)| name);
+    let match_idents = accepted.iter().map(|(_, variant)| variant);
+
+    quote! {
+        impl<'de> ::serde::Deserialize<'de> for #ident {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                let raw = <::std::string::String as ::serde::Deserialize>::deserialize(deserializer)?;
+                let lower = raw.to_lowercase();
+                match lower.as_str() {
+                    #(#match_names => ::std::result::Result::Ok(#ident::#match_idents),)*
+                    _ => {
+                        ::dynisland_core::abi::log::warn!(
+                            "unrecognized {} value `{}`, using the default",
+                            ::std::stringify!(#ident), raw
+                        );
+                        ::std::result::Result::Ok(#ident::#default_variant)
+                    }
+                }
+            }
+        }
+    }
+}
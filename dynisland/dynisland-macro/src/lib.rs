@@ -3,6 +3,7 @@ use quote::TokenStreamExt;
 use syn::{parse_macro_input, DeriveInput};
 
 mod config;
+mod config_enum;
 
 #[proc_macro_derive(MultiWidgetConfig, attributes(child_only))]
 pub fn multi_widget_config_derive(input: TokenStream) -> TokenStream {
@@ -21,3 +22,9 @@ pub fn opt_deserialize_derive(input: TokenStream) -> TokenStream {
     tokens.append_all(config::derive_config_de(input.clone()));
     tokens.into()
 }
+
+#[proc_macro_derive(ConfigEnum, attributes(config))]
+pub fn config_enum_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    config_enum::derive_config_enum(input).into()
+}
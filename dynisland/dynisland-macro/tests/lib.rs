@@ -1,6 +1,41 @@
-use dynisland_macro::{MultiWidgetConfig, OptDeserializeConfig};
+use std::collections::HashMap;
+
+use dynisland_macro::{ConfigEnum, MultiWidgetConfig, OptDeserializeConfig};
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, PartialEq, Clone, Serialize, ConfigEnum)]
+enum Alignment {
+    Leading,
+    #[config(alias = "centered")]
+    Middle,
+    #[config(default)]
+    Trailing,
+}
+
+#[test]
+fn test_config_enum_case_insensitive_and_alias() {
+    assert_eq!(
+        serde_json::from_str::<Alignment>(r#""leading""#).unwrap(),
+        Alignment::Leading
+    );
+    assert_eq!(
+        serde_json::from_str::<Alignment>(r#""LEADING""#).unwrap(),
+        Alignment::Leading
+    );
+    assert_eq!(
+        serde_json::from_str::<Alignment>(r#""Centered""#).unwrap(),
+        Alignment::Middle
+    );
+}
+
+#[test]
+fn test_config_enum_unknown_falls_back_to_default() {
+    assert_eq!(
+        serde_json::from_str::<Alignment>(r#""sideways""#).unwrap(),
+        Alignment::Trailing
+    );
+}
+
 #[derive(Clone, PartialEq, Serialize, MultiWidgetConfig, OptDeserializeConfig, Debug)]
 pub struct TestConfig {
     #[serde(skip_serializing)]
@@ -38,6 +73,61 @@ pub struct WinPos {
     pub(crate) layer: u64,
 }
 
+#[derive(Clone, PartialEq, Serialize, MultiWidgetConfig, OptDeserializeConfig, Debug)]
+pub struct WrappedConfig {
+    pub(crate) name: String,
+    #[deserialize_struct(DeWinPos)]
+    pub(crate) positions: Vec<WinPos>,
+    #[deserialize_struct(DeWinPos)]
+    pub(crate) fallback: Option<WinPos>,
+    #[deserialize_struct(DeWinPos)]
+    pub(crate) named: HashMap<String, WinPos>,
+}
+impl Default for WrappedConfig {
+    fn default() -> Self {
+        Self {
+            name: "test".to_string(),
+            positions: vec![WinPos { layer: 1 }],
+            fallback: None,
+            named: HashMap::new(),
+        }
+    }
+}
+
+#[test]
+fn test_config_wrapped_nested() {
+    let test_opt: DeWrappedConfigMain = serde_json::from_str(
+        r#"{
+        "positions": [{"layer": 1}],
+        "windows": {
+            "window": [
+                {
+                    "name": "w",
+                    "positions": [{"layer": 5}, {"layer": 6}],
+                    "fallback": {"layer": 9},
+                    "named": {"a": {"layer": 2}}
+                }
+            ],
+            "window2": [
+                {}
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    let test_main = test_opt.into_main_config();
+
+    let w = test_main.get_for_window("window", 0);
+    assert_eq!(w.positions, vec![WinPos { layer: 5 }, WinPos { layer: 6 }]);
+    assert_eq!(w.fallback, Some(WinPos { layer: 9 }));
+    assert_eq!(w.named.get("a"), Some(&WinPos { layer: 2 }));
+
+    // a window that doesn't override a wrapped field keeps the base config's value
+    let w2 = test_main.get_for_window("window2", 0);
+    assert_eq!(w2.positions, vec![WinPos { layer: 1 }]);
+    assert_eq!(w2.fallback, None);
+}
+
 #[test]
 fn test_multi_widget_config_derive() {
     let test = TestConfig::default();
@@ -105,6 +195,119 @@ fn test_parse_serde_full() {
     assert_eq!(test1, test_main.get_for_window("window2", 0));
 }
 
+#[test]
+fn test_parse_serde_tolerant() {
+    let test_opt: DeTestConfigMain = serde_json::from_str(
+        r#"{
+        "windows": {
+            "window": [
+                {
+                    "max_width": "not a number",
+                    "scrolling_speed": "default",
+                    "unknown_field": 42,
+                    "minimal_image": "image-missing-symbolic1"
+                }
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    let test_main = test_opt.into_main_config();
+    let resolved = test_main.get_for_window("window", 0);
+    let default = TestConfig::default();
+    // an invalid value, the "default" sentinel, and an unknown key all fall back to the
+    // default/are ignored instead of failing the whole config
+    assert_eq!(resolved.max_width, default.max_width);
+    assert_eq!(resolved.scrolling_speed, default.scrolling_speed);
+    assert_eq!(resolved.minimal_image, "image-missing-symbolic1");
+}
+
+#[test]
+fn test_config_presets() {
+    let test_opt: DeTestConfigMain = serde_json::from_str(
+        r#"{
+        "presets": {
+            "night": {
+                "scrolling": false,
+                "max_width": 3000
+            }
+        },
+        "windows": {
+            "window": [
+                {
+                    "preset": "night",
+                    "minimal_image": "moon-symbolic"
+                }
+            ],
+            "window2": [
+                {}
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    let test_main = test_opt.into_main_config();
+    let default = TestConfig::default();
+
+    // windows that opt into a preset merge base -> preset -> per-window overrides
+    let night = test_main.get_for_window("window", 0);
+    assert!(!night.scrolling);
+    assert_eq!(night.max_width, 3000);
+    assert_eq!(night.minimal_image, "moon-symbolic");
+
+    // windows that don't name a preset are unaffected by it
+    let plain = test_main.get_for_window("window2", 0);
+    assert_eq!(plain.scrolling, default.scrolling);
+    assert_eq!(plain.max_width, default.max_width);
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Deserialize, Serialize, OptDeserializeConfig)]
+pub struct SharedColors {
+    pub(crate) normal_color: String,
+    pub(crate) low_color: String,
+}
+
+#[derive(Clone, PartialEq, Serialize, MultiWidgetConfig, OptDeserializeConfig, Debug)]
+pub struct FlattenConfig {
+    pub(crate) name: String,
+    #[deserialize_struct(DeSharedColors)]
+    #[config(flatten)]
+    pub(crate) colors: SharedColors,
+}
+impl Default for FlattenConfig {
+    fn default() -> Self {
+        Self {
+            name: "test".to_string(),
+            colors: SharedColors::default(),
+        }
+    }
+}
+
+#[test]
+fn test_config_flatten() {
+    let test_opt: DeFlattenConfigMain = serde_json::from_str(
+        r#"{
+        "normal_color": "#ffffff",
+        "windows": {
+            "window": [
+                {
+                    "name": "w",
+                    "low_color": "#ff0000"
+                }
+            ]
+        }
+    }"#,
+    )
+    .unwrap();
+    let test_main = test_opt.into_main_config();
+    let resolved = test_main.get_for_window("window", 0);
+    // a flattened field is read from the top level of the object instead of a nested
+    // `"colors": { .. }` object, both for the base config and for per-window overrides
+    assert_eq!(resolved.name, "w");
+    assert_eq!(resolved.colors.normal_color, "#ffffff");
+    assert_eq!(resolved.colors.low_color, "#ff0000");
+}
+
 #[test]
 fn test_serialize_default() {
     let mut conf = TestConfigMain::default();
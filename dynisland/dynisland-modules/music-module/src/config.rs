@@ -2,22 +2,19 @@ use std::{collections::HashMap, rc::Rc};
 
 use dynisland_core::{
     abi::module::ActivityIdentifier,
-    d_macro::{MultiWidgetConfig, OptDeserializeConfig},
+    d_macro::{ConfigEnum, MultiWidgetConfig, OptDeserializeConfig},
     dynamic_activity::DynamicActivity,
 };
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use tokio::sync::Mutex;
 
-#[derive(Debug, Serialize, Clone, Deserialize)]
-#[serde(tag = "ArtistMode")]
+#[derive(Debug, Serialize, Clone, ConfigEnum)]
 pub enum ArtistMode {
-    #[serde(alias = "leading")]
     Leading,
-    #[serde(alias = "trailing")]
     Trailing,
-    #[serde(alias = "centered")]
+    #[config(alias = "centered")]
     Bottom,
-    #[serde(alias = "none")]
+    #[config(default)]
     None,
 }
 
@@ -0,0 +1,158 @@
+//! UPS backend speaking the `apcupsd` NIS protocol (the same protocol `apcaccess status` uses)
+//! over TCP, so a networked/serial UPS that UPower never sees can expose roughly the same
+//! `state()`/`percentage()`/`time_to_empty()` surface as an `org.freedesktop.UPower` [`Device`].
+//!
+//! [`Device`]: crate::upower::device::Device
+
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    backend::BatteryDevice,
+    upower::device::{State, StatisticsEntry},
+};
+
+/// A connection to an `apcupsd` daemon's NIS socket.
+#[derive(Debug, Clone)]
+pub struct ApcAccess {
+    host: String,
+    port: u16,
+}
+
+impl ApcAccess {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+        }
+    }
+
+    /// Opens a fresh connection, sends the `status` request, and returns the parsed
+    /// `KEY : VALUE` pairs apcupsd reports (`BCHARGE`, `STATUS`, `TIMELEFT`, `LINEV`, `LOADPCT`, ...).
+    pub async fn status(&self) -> std::io::Result<HashMap<String, String>> {
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        write_record(&mut stream, b"status").await?;
+        let mut status = HashMap::new();
+        loop {
+            let record = read_record(&mut stream).await?;
+            if record.is_empty() {
+                break;
+            }
+            let line = String::from_utf8_lossy(&record);
+            if let Some((key, value)) = line.split_once(':') {
+                status.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+        Ok(status)
+    }
+
+    /// Percentage charge, from the `BCHARGE` key (`0.0` if apcupsd didn't report it).
+    pub async fn percentage(&self) -> std::io::Result<f64> {
+        let status = self.status().await?;
+        Ok(parse_leading_f64(status.get("BCHARGE")))
+    }
+
+    /// Current [`State`], derived from the `STATUS` key (`ONBATT` => discharging, `CHARGING`
+    /// => charging). Stock apcupsd reports `ONLINE` for normal mains operation regardless of
+    /// whether the battery is still topping up, so `ONLINE` is `Charging` unless `BCHARGE`
+    /// is already (near) full.
+    pub async fn state(&self) -> std::io::Result<State> {
+        let status = self.status().await?;
+        Ok(match status.get("STATUS").map(String::as_str) {
+            Some("ONLINE") => {
+                if parse_leading_f64(status.get("BCHARGE")) >= 99.0 {
+                    State::FullyCharged
+                } else {
+                    State::Charging
+                }
+            }
+            Some("ONBATT") => State::Discharging,
+            Some("CHARGING") => State::Charging,
+            _ => State::Unknown,
+        })
+    }
+
+    /// Time remaining on battery, from the `TIMELEFT` key (minutes of runtime).
+    pub async fn time_to_empty(&self) -> std::io::Result<Duration> {
+        let status = self.status().await?;
+        let minutes = parse_leading_f64(status.get("TIMELEFT"));
+        Ok(Duration::from_secs_f64(minutes.max(0.0) * 60.0))
+    }
+
+    /// Line voltage and load percentage, surfaced like [`Device::get_statistics`].
+    ///
+    /// [`Device::get_statistics`]: crate::upower::device::Device::get_statistics
+    #[allow(dead_code)]
+    pub async fn statistics(&self) -> std::io::Result<Vec<StatisticsEntry>> {
+        let status = self.status().await?;
+        Ok(vec![
+            StatisticsEntry {
+                value: parse_leading_f64(status.get("LINEV")),
+                accuracy: 100.0,
+            },
+            StatisticsEntry {
+                value: parse_leading_f64(status.get("LOADPCT")),
+                accuracy: 100.0,
+            },
+        ])
+    }
+}
+
+#[async_trait]
+impl BatteryDevice for ApcAccess {
+    async fn is_available(&self) -> bool {
+        self.status().await.is_ok()
+    }
+
+    async fn percentage(&self) -> anyhow::Result<f64> {
+        Ok(ApcAccess::percentage(self).await?)
+    }
+
+    async fn state(&self) -> anyhow::Result<State> {
+        Ok(ApcAccess::state(self).await?)
+    }
+
+    // apcupsd doesn't report a charging ETA, only `TIMELEFT` on battery
+    async fn time_to_full(&self) -> anyhow::Result<Duration> {
+        Ok(Duration::ZERO)
+    }
+
+    async fn time_to_empty(&self) -> anyhow::Result<Duration> {
+        Ok(ApcAccess::time_to_empty(self).await?)
+    }
+
+    // apcupsd reports instantaneous charge, not design capacity, so it has no way to derive health
+    async fn health(&self) -> anyhow::Result<f64> {
+        Err(anyhow::anyhow!("apcupsd doesn't report battery health"))
+    }
+}
+
+fn parse_leading_f64(value: Option<&String>) -> f64 {
+    value
+        .and_then(|v| v.split_whitespace().next())
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.0)
+}
+
+async fn write_record(stream: &mut TcpStream, payload: &[u8]) -> std::io::Result<()> {
+    let len = (payload.len() as u16).to_be_bytes();
+    stream.write_all(&len).await?;
+    stream.write_all(payload).await
+}
+
+async fn read_record(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 2];
+    stream.read_exact(&mut len_buf).await?;
+    let len = u16::from_be_bytes(len_buf) as usize;
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(buf)
+}
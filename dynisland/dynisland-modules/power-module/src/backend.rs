@@ -0,0 +1,188 @@
+//! Backend abstraction so the producer can read a battery's state from either UPower or sysfs,
+//! whichever is actually available on the system.
+//!
+//! `UPowerProxy`/`Device` assume a running system D-Bus with UPower on it, which isn't a given on
+//! minimal systems (containers, some embedded builds). [`SysfsBattery`] reads the same
+//! information straight out of `/sys/class/power_supply` instead.
+
+use std::{path::PathBuf, time::Duration};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::upower::device::{Device, State};
+
+/// The handful of things the producer needs from a battery, regardless of where they come from.
+#[async_trait]
+pub trait BatteryDevice: Send + Sync {
+    /// Whether this device currently exists/is reachable.
+    async fn is_available(&self) -> bool;
+    /// State of charge, 0.0-100.0.
+    async fn percentage(&self) -> anyhow::Result<f64>;
+    async fn state(&self) -> anyhow::Result<State>;
+    async fn time_to_full(&self) -> anyhow::Result<Duration>;
+    async fn time_to_empty(&self) -> anyhow::Result<Duration>;
+    /// Capacity health, 0.0-100.0 (how much of the design capacity the battery can still hold),
+    /// or an error if this backend has no way to report it.
+    async fn health(&self) -> anyhow::Result<f64>;
+}
+
+#[async_trait]
+impl BatteryDevice for Device {
+    async fn is_available(&self) -> bool {
+        self.proxy.native_path().await.is_ok()
+    }
+
+    async fn percentage(&self) -> anyhow::Result<f64> {
+        Ok(self.proxy.percentage().await?)
+    }
+
+    async fn state(&self) -> anyhow::Result<State> {
+        Ok(Device::state(self).await?)
+    }
+
+    async fn time_to_full(&self) -> anyhow::Result<Duration> {
+        Ok(Device::time_to_full(self).await?)
+    }
+
+    async fn time_to_empty(&self) -> anyhow::Result<Duration> {
+        Ok(Device::time_to_empty(self).await?)
+    }
+
+    async fn health(&self) -> anyhow::Result<f64> {
+        Ok(Device::health(self).await?)
+    }
+}
+
+/// Which backend a battery should use. `Auto` probes UPower first and falls back to sysfs if the
+/// system bus can't be reached.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    #[default]
+    Auto,
+    UPower,
+    Sysfs,
+}
+
+const POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+/// Reads battery state directly from `/sys/class/power_supply/<name>`.
+pub struct SysfsBattery {
+    path: PathBuf,
+}
+
+impl SysfsBattery {
+    pub fn new(name: &str) -> Self {
+        Self {
+            path: PathBuf::from(POWER_SUPPLY_ROOT).join(name),
+        }
+    }
+
+    /// Lists every supply under `/sys/class/power_supply` whose `type` is `Battery` (e.g. `BAT0`,
+    /// `BAT1`), so the sysfs backend can feed the existing per-window multi-activity layout the
+    /// same way `UPowerProxy::enumerate_devices` does.
+    pub fn enumerate() -> std::io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(POWER_SUPPLY_ROOT)? {
+            let entry = entry?;
+            let is_battery = std::fs::read_to_string(entry.path().join("type"))
+                .map(|kind| kind.trim() == "Battery")
+                .unwrap_or(false);
+            if is_battery {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn read(&self, attr: &str) -> std::io::Result<String> {
+        Ok(std::fs::read_to_string(self.path.join(attr))?
+            .trim()
+            .to_string())
+    }
+
+    fn read_f64(&self, attr: &str) -> anyhow::Result<f64> {
+        Ok(self.read(attr)?.parse()?)
+    }
+
+    /// `charge_now`/`energy_now`, in µAh/µWh, whichever this supply exposes.
+    fn energy_now(&self) -> anyhow::Result<f64> {
+        self.read_f64("charge_now")
+            .or_else(|_| self.read_f64("energy_now"))
+    }
+
+    /// `charge_full`/`energy_full`, in µAh/µWh, whichever this supply exposes.
+    fn energy_full(&self) -> anyhow::Result<f64> {
+        self.read_f64("charge_full")
+            .or_else(|_| self.read_f64("energy_full"))
+    }
+
+    /// `current_now`/`power_now`, in µA/µW, whichever this supply exposes.
+    fn power_now(&self) -> anyhow::Result<f64> {
+        self.read_f64("current_now")
+            .or_else(|_| self.read_f64("power_now"))
+    }
+
+    /// `charge_full_design`/`energy_full_design`, in µAh/µWh, whichever this supply exposes.
+    fn energy_full_design(&self) -> anyhow::Result<f64> {
+        self.read_f64("charge_full_design")
+            .or_else(|_| self.read_f64("energy_full_design"))
+    }
+}
+
+#[async_trait]
+impl BatteryDevice for SysfsBattery {
+    async fn is_available(&self) -> bool {
+        self.path.join("capacity").exists()
+    }
+
+    async fn percentage(&self) -> anyhow::Result<f64> {
+        self.read_f64("capacity")
+    }
+
+    async fn state(&self) -> anyhow::Result<State> {
+        Ok(match self.read("status")?.as_str() {
+            "Charging" => State::Charging,
+            "Discharging" => State::Discharging,
+            "Full" => State::FullyCharged,
+            "Not charging" => State::PendingCharge,
+            _ => State::Unknown,
+        })
+    }
+
+    async fn time_to_full(&self) -> anyhow::Result<Duration> {
+        if !matches!(self.state().await?, State::Charging) {
+            return Ok(Duration::ZERO);
+        }
+        // same approach i3status-style blocks use: remaining energy over the current draw
+        remaining_to_duration(self.energy_full()? - self.energy_now()?, self.power_now()?)
+    }
+
+    async fn time_to_empty(&self) -> anyhow::Result<Duration> {
+        if !matches!(self.state().await?, State::Discharging) {
+            return Ok(Duration::ZERO);
+        }
+        remaining_to_duration(self.energy_now()?, self.power_now()?)
+    }
+
+    async fn health(&self) -> anyhow::Result<f64> {
+        let full = self.energy_full()?;
+        let design = self.energy_full_design()?;
+        if design <= 0.0 {
+            anyhow::bail!("energy_full_design is zero");
+        }
+        Ok(full / design * 100.0)
+    }
+}
+
+fn remaining_to_duration(energy_remaining: f64, power: f64) -> anyhow::Result<Duration> {
+    if power <= 0.0 {
+        return Ok(Duration::ZERO);
+    }
+    Ok(Duration::from_secs_f64(
+        (energy_remaining / power * 3600.0).max(0.0),
+    ))
+}
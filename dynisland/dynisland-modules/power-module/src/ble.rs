@@ -0,0 +1,101 @@
+//! Bluetooth LE battery backend.
+//!
+//! UPower doesn't expose a battery level for most BLE peripherals (headphones, game controllers,
+//! keyboards) unless a helper daemon bridges them, so for those devices this module talks to the
+//! standard GATT Battery Service directly instead of going through UPower.
+
+use bluest::{btuuid::bluetooth_uuid_from_u16, Adapter, Device, DeviceId};
+use futures::{Stream, StreamExt};
+
+/// The GATT Battery Service (`0x180F`).
+const BATTERY_SERVICE: bluest::Uuid = bluetooth_uuid_from_u16(0x180f);
+/// The Battery Level characteristic (`0x2A19`): a single unsigned byte, 0-255, read as a percentage.
+const BATTERY_LEVEL: bluest::Uuid = bluetooth_uuid_from_u16(0x2a19);
+
+/// A connected handle to a BLE peripheral's Battery Service.
+pub struct BleBattery {
+    device: Device,
+}
+
+impl BleBattery {
+    /// Waits for the default adapter to become available, then reconnects to the peripheral
+    /// remembered by `device_id` (as returned by [`list_devices`]).
+    pub async fn connect(device_id: &str) -> anyhow::Result<Self> {
+        let adapter = Adapter::default()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("no bluetooth adapter available"))?;
+        adapter.wait_available().await?;
+        let id = DeviceId::from_str(device_id)
+            .map_err(|_| anyhow::anyhow!("invalid ble device id: {device_id}"))?;
+        let device = adapter.open_device(&id).await?;
+        adapter.connect_device(&device).await?;
+        Ok(Self { device })
+    }
+
+    /// Reads the Battery Level characteristic once, as a 0-100 percentage.
+    pub async fn percentage(&self) -> anyhow::Result<u8> {
+        let value = self.read_level().await?;
+        Ok(value)
+    }
+
+    /// Streams the Battery Level characteristic's notifications, decoded into a 0-100
+    /// percentage. If the characteristic doesn't support notifications, falls back to polling it
+    /// every `poll_interval`, matching the cadence the UPower path refreshes on.
+    pub async fn watch_percentage(
+        &self,
+        poll_interval: std::time::Duration,
+    ) -> anyhow::Result<impl Stream<Item = u8> + '_> {
+        let characteristic = self.battery_level_characteristic().await?;
+        if characteristic.supports_notify().await.unwrap_or(false) {
+            let notifications = characteristic.notify().await?;
+            Ok(futures::stream::Either::Left(
+                notifications.filter_map(|value| async move { value.ok().map(decode_level) }),
+            ))
+        } else {
+            Ok(futures::stream::Either::Right(
+                tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(poll_interval))
+                    .filter_map(move |_| {
+                        let characteristic = characteristic.clone();
+                        async move { characteristic.read().await.ok().map(decode_level) }
+                    }),
+            ))
+        }
+    }
+
+    async fn battery_level_characteristic(&self) -> anyhow::Result<bluest::Characteristic> {
+        let services = self.device.discover_services().await?;
+        let service = services
+            .into_iter()
+            .find(|s| s.uuid() == BATTERY_SERVICE)
+            .ok_or_else(|| anyhow::anyhow!("device has no battery service"))?;
+        let characteristics = service.discover_characteristics().await?;
+        characteristics
+            .into_iter()
+            .find(|c| c.uuid() == BATTERY_LEVEL)
+            .ok_or_else(|| anyhow::anyhow!("device has no battery level characteristic"))
+    }
+
+    async fn read_level(&self) -> anyhow::Result<u8> {
+        let characteristic = self.battery_level_characteristic().await?;
+        Ok(decode_level(characteristic.read().await?))
+    }
+}
+
+fn decode_level(value: Vec<u8>) -> u8 {
+    value.first().copied().unwrap_or(0)
+}
+
+/// Lists every BLE device currently connected to the adapter, as `id: name` strings, for the
+/// `list-ble` cli command. Devices that are bonded but not connected are not listed.
+pub async fn list_devices() -> anyhow::Result<Vec<String>> {
+    let adapter = Adapter::default()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("no bluetooth adapter available"))?;
+    adapter.wait_available().await?;
+    let mut devices = Vec::new();
+    for device in adapter.connected_devices().await? {
+        let name = device.name().unwrap_or_else(|_| "?".to_string());
+        devices.push(format!("{}: {}", device.id().to_string(), name));
+    }
+    Ok(devices)
+}
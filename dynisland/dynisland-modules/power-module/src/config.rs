@@ -9,7 +9,31 @@ use gtk::{prelude::*, subclass::prelude::*};
 use serde::Serialize;
 use tokio::sync::Mutex;
 
-use crate::widget::{battery::Battery, compact::Compact, expanded::Expanded, minimal::Minimal};
+use crate::{
+    backend::BackendKind,
+    widget::{battery::Battery, compact::Compact, expanded::Expanded, minimal::Minimal},
+};
+
+/// Selects which backend a [`PowerConfig`] reads its battery from.
+#[derive(Debug, Serialize, serde::Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum BatterySource {
+    /// Read `battery` from UPower (or sysfs, per `backend`), same as leaving `source` unset.
+    UPower { battery: String },
+    /// Read a Bluetooth LE peripheral's Battery Service directly, bypassing UPower. `device_id`
+    /// is the string returned by the `list-ble` cli command.
+    Ble { device_id: String },
+    /// Read an `apcupsd` daemon's NIS socket instead of a local battery.
+    Ups { host: String, port: u16 },
+}
+
+impl Default for BatterySource {
+    fn default() -> Self {
+        BatterySource::UPower {
+            battery: String::new(),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, MultiWidgetConfig, OptDeserializeConfig, Clone)]
 #[serde(default)]
@@ -24,6 +48,12 @@ pub struct PowerConfig {
     pub(crate) background_color: String,
     pub(crate) max_duration_secs: u32,
     pub(crate) draw_bars: bool,
+    /// Which backend to read the battery from. Defaults to the UPower/sysfs device named by
+    /// `battery`; set to `Ups` to read an `apcupsd` daemon, or `Ble` to read a Bluetooth LE
+    /// peripheral's Battery Service, instead.
+    pub(crate) source: BatterySource,
+    /// Forces UPower or sysfs instead of probing for UPower and falling back to sysfs.
+    pub(crate) backend: BackendKind,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -42,6 +72,8 @@ impl Default for PowerConfig {
             background_color: "#E6E6E699".to_string(),
             max_duration_secs: 36000,
             draw_bars: true,
+            source: BatterySource::default(),
+            backend: BackendKind::default(),
         }
     }
 }
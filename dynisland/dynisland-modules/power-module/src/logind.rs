@@ -0,0 +1,132 @@
+//! `org.freedesktop.login1` integration so the producer can pause polling while the session is
+//! locked or the system is about to suspend, instead of wastefully refreshing a history graph
+//! nobody can see (and leaving a stale one across a suspend/resume gap).
+
+use futures::StreamExt;
+use tokio::sync::watch;
+use zbus::{proxy, zvariant::OwnedObjectPath, Connection};
+
+use dynisland_core::{abi::log, base_module::ProducerRuntime};
+
+#[proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait LoginManager {
+    fn get_session_by_pid(&self, pid: u32) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+#[proxy(interface = "org.freedesktop.login1.Session", default_service = "org.freedesktop.login1")]
+trait LoginSession {
+    #[zbus(signal)]
+    fn lock(&self) -> zbus::Result<()>;
+    #[zbus(signal)]
+    fn unlock(&self) -> zbus::Result<()>;
+}
+
+/// Tracks whether the current session is active (not locked, not suspending), so device update
+/// loops can pause while it's not.
+///
+/// If logind isn't reachable the session is just reported as always active, since there's nothing
+/// useful to watch.
+#[derive(Clone)]
+pub struct SessionMonitor {
+    active: watch::Receiver<bool>,
+}
+
+impl SessionMonitor {
+    /// A monitor that never pauses anything, for when there's no D-Bus connection to watch
+    /// logind over in the first place (e.g. the sysfs-only fallback).
+    pub fn always_active() -> Self {
+        let (_tx, rx) = watch::channel(true);
+        Self { active: rx }
+    }
+
+    /// Subscribes to logind's `PrepareForSleep` and the current session's `Lock`/`Unlock`
+    /// signals on `rt`, so the monitor keeps running (and cleans itself up) alongside the other
+    /// producers.
+    pub async fn spawn(rt: &ProducerRuntime, conn: &Connection) -> Self {
+        let (tx, rx) = watch::channel(true);
+        if let Err(err) = Self::watch(rt, conn.clone(), tx).await {
+            log::warn!(
+                "logind session monitoring unavailable ({}), producers will stay always-active",
+                err
+            );
+        }
+        Self { active: rx }
+    }
+
+    /// A receiver that reports `true` while the session is active. Clone freely; each producer
+    /// loop should hold its own.
+    pub fn receiver(&self) -> watch::Receiver<bool> {
+        self.active.clone()
+    }
+
+    async fn watch(
+        rt: &ProducerRuntime,
+        conn: Connection,
+        tx: watch::Sender<bool>,
+    ) -> zbus::Result<()> {
+        let manager = LoginManagerProxy::new(&conn).await?;
+        let session_path = manager.get_session_by_pid(std::process::id()).await?;
+        let session = LoginSessionProxy::builder(&conn)
+            .path(session_path)?
+            .build()
+            .await?;
+
+        let mut cleanup_tx = rt.get_cleanup_notifier();
+        rt.handle().spawn(async move {
+            let mut sleeps = match manager.receive_prepare_for_sleep().await {
+                Ok(s) => s,
+                Err(err) => {
+                    log::warn!("couldn't watch PrepareForSleep: {}", err);
+                    return;
+                }
+            };
+            let mut locks = match session.receive_lock().await {
+                Ok(s) => s,
+                Err(err) => {
+                    log::warn!("couldn't watch session Lock: {}", err);
+                    return;
+                }
+            };
+            let mut unlocks = match session.receive_unlock().await {
+                Ok(s) => s,
+                Err(err) => {
+                    log::warn!("couldn't watch session Unlock: {}", err);
+                    return;
+                }
+            };
+            loop {
+                tokio::select! {
+                    clean = cleanup_tx.recv() => {
+                        if let Ok(sender) = clean {
+                            sender.send(()).unwrap();
+                            return;
+                        }
+                    }
+                    signal = sleeps.next() => {
+                        let Some(signal) = signal else { return };
+                        if let Ok(args) = signal.args() {
+                            // `start == true` means the system is about to suspend
+                            let _ = tx.send(!args.start);
+                        }
+                    }
+                    signal = locks.next() => {
+                        if signal.is_none() { return; }
+                        let _ = tx.send(false);
+                    }
+                    signal = unlocks.next() => {
+                        if signal.is_none() { return; }
+                        let _ = tx.send(true);
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+}
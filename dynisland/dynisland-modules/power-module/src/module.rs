@@ -23,6 +23,7 @@ use dynisland_core::{
 };
 #[cfg(not(feature = "embedded"))]
 use env_logger::Env;
+use futures::StreamExt;
 use ron::ser::PrettyConfig;
 use tokio::sync::{
     mpsc::{UnboundedReceiver, UnboundedSender},
@@ -31,7 +32,10 @@ use tokio::sync::{
 use zbus::{zvariant::OwnedObjectPath, Connection};
 
 use crate::{
-    config::{DePowerConfigMain, PowerConfig, PowerConfigMain},
+    apc::ApcAccess,
+    backend::{BackendKind, BatteryDevice, SysfsBattery},
+    config::{BatterySource, DePowerConfigMain, PowerConfig, PowerConfigMain},
+    logind::SessionMonitor,
     upower::{self, device::Device, proxy::device::DeviceProxyBlocking},
     NAME,
 };
@@ -40,7 +44,12 @@ pub struct PowerModule {
     pub(crate) base_module: BaseModule<PowerModule>,
     pub(crate) producers_rt: ProducerRuntime,
     pub(crate) config: PowerConfigMain,
-    pub(crate) connection: zbus::Connection,
+    /// `None` when the system D-Bus (and so UPower) isn't reachable; activities fall back to the
+    /// sysfs backend in that case.
+    pub(crate) connection: Option<zbus::Connection>,
+    /// Tracks whether the session is locked/suspending, so the per-device loops in `producer` can
+    /// pause instead of polling a battery nobody's looking at.
+    pub(crate) session_monitor: SessionMonitor,
 }
 
 #[sabi_extern_fn]
@@ -60,9 +69,25 @@ pub fn new(app_send: RSender<UIServerCommand>) -> RResult<ModuleType, RBoxError>
         .windows
         .insert("".to_string(), vec![PowerConfig::default()]);
 
+    // UPower is the preferred source, but isn't always available (containers, minimal installs,
+    // some embedded builds), so probe for it and transparently fall back to reading
+    // `/sys/class/power_supply` directly instead of hard-failing module load.
     let connection = match producers_rt.handle().block_on(zbus::Connection::system()) {
-        Ok(c) => c,
-        Err(err) => return RErr(RBoxError::new(err)),
+        Ok(c) => Some(c),
+        Err(err) => {
+            log::warn!(
+                "no system D-Bus connection available ({}), falling back to the sysfs battery backend",
+                err
+            );
+            None
+        }
+    };
+
+    let session_monitor = match &connection {
+        Some(conn) => producers_rt
+            .handle()
+            .block_on(SessionMonitor::spawn(&producers_rt, conn)),
+        None => SessionMonitor::always_active(),
     };
 
     let this = PowerModule {
@@ -70,6 +95,7 @@ pub fn new(app_send: RSender<UIServerCommand>) -> RResult<ModuleType, RBoxError>
         producers_rt,
         config,
         connection,
+        session_monitor,
     };
     ROk(SabiModule_TO::from_value(this, TD_CanDowncast))
 }
@@ -128,11 +154,55 @@ impl SabiModule for PowerModule {
                 }
                 ROk(res.into())
             }
+            Some(&"list-ble") => {
+                match self
+                    .producers_rt
+                    .handle()
+                    .block_on(crate::ble::list_devices())
+                {
+                    Ok(devices) if devices.is_empty() => {
+                        ROk("No bluetooth devices found".to_string().into())
+                    }
+                    Ok(devices) => ROk(devices.join("\n").into()),
+                    Err(err) => RErr(RBoxError::from_fmt(&format!(
+                        "Error while listing bluetooth devices: {}",
+                        err
+                    ))),
+                }
+            }
+            Some(&"ups-status") => {
+                let host = command_vec.get(1).copied().unwrap_or("127.0.0.1");
+                let port: u16 = command_vec
+                    .get(2)
+                    .and_then(|p| p.parse().ok())
+                    .unwrap_or(3551);
+                match self
+                    .producers_rt
+                    .handle()
+                    .block_on(ApcAccess::new(host, port).status())
+                {
+                    Ok(status) => {
+                        let mut keys: Vec<_> = status.keys().collect();
+                        keys.sort();
+                        let mut res = String::new();
+                        for key in keys {
+                            res += &format!("{key}: {}\n", status[key]);
+                        }
+                        ROk(res.into())
+                    }
+                    Err(err) => RErr(RBoxError::from_fmt(&format!(
+                        "Error while reading UPS status from {host}:{port}: {}",
+                        err
+                    ))),
+                }
+            }
             Some(&"help") | None => {
                 #[rustfmt::skip]
                 return ROk(
-r"Commands: 
-    list: list the available batteries"
+r"Commands:
+    list: list the available batteries
+    list-ble: list the available bluetooth devices
+    ups-status [host] [port]: dump the raw apcupsd key/values (defaults to 127.0.0.1:3551)"
                 .into());
             }
             _ => RErr(RBoxError::from_fmt(&format!(
@@ -318,18 +388,193 @@ fn producer(module: &PowerModule) {
             .unwrap();
         let time_to = dyn_act.blocking_lock().get_property_any("time-to").unwrap();
         let points = dyn_act.blocking_lock().get_property_any("points").unwrap();
+        let health_prop = dyn_act.blocking_lock().get_property_any("health").unwrap();
+
+        if let BatterySource::Ble { device_id } = &activity_config.source {
+            // a BLE peripheral is its own independent data source, takes priority over the
+            // UPower/sysfs `battery`/`backend` config either way, same as `Ups` below.
+            let device_id = device_id.clone();
+            let activity_id = activity_id.clone();
+            let register_tx = register_tx.clone();
+            let active = module.session_monitor.receiver();
+            // mirrors `device_change_updater`'s `keep_registered` (`!hide_if_missing`): a BLE
+            // peripheral has no "display device" fallback to show instead, so honoring
+            // `hide_if_missing = false` here just means leaving the activity registered (with
+            // its last known reading) instead of hiding it while the peripheral is out of range
+            let keep_registered = !activity_config.hide_if_missing;
+            rt.handle().spawn(async move {
+                let mut was_available = false;
+                loop {
+                    if !*active.borrow() {
+                        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                        continue;
+                    }
+                    let ble = match crate::ble::BleBattery::connect(&device_id).await {
+                        Ok(ble) => ble,
+                        Err(err) => {
+                            log::debug!("ble battery {} unavailable: {}", device_id, err);
+                            if was_available {
+                                was_available = false;
+                                if !keep_registered {
+                                    register_tx.send((activity_id.clone(), false)).unwrap();
+                                }
+                            }
+                            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                            continue;
+                        }
+                    };
+                    if !was_available {
+                        register_tx.send((activity_id.clone(), true)).unwrap();
+                        was_available = true;
+                    }
+                    // the GATT Battery Service has no charging/state characteristic, so
+                    // `charging` and `time-to` stay at their property defaults here; only
+                    // `percentage` is ever live for a BLE source.
+                    if let Ok(percentage) = ble.percentage().await {
+                        percentage_prop.lock().await.set(percentage as f64 / 100.0).unwrap();
+                    }
+                    let Ok(mut updates) = ble.watch_percentage(std::time::Duration::from_secs(30)).await
+                    else {
+                        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                        continue;
+                    };
+                    while *active.borrow() {
+                        match updates.next().await {
+                            Some(percentage) => {
+                                percentage_prop.lock().await.set(percentage as f64 / 100.0).unwrap();
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            });
+            continue;
+        }
+
+        if let BatterySource::Ups { host, port } = &activity_config.source {
+            // a UPS is its own independent data source, takes priority over the
+            // UPower/sysfs `battery`/`backend` config either way.
+            let apc = ApcAccess::new(host.clone(), *port);
+            let activity_id = activity_id.clone();
+            let register_tx = register_tx.clone();
+            let active = module.session_monitor.receiver();
+            rt.handle().spawn(async move {
+                let mut was_available = false;
+                loop {
+                    if !*active.borrow() {
+                        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                        continue;
+                    }
+                    let available = apc.is_available().await;
+                    if available != was_available {
+                        register_tx.send((activity_id.clone(), available)).unwrap();
+                        was_available = available;
+                    }
+                    if available {
+                        if let Ok(percentage) = apc.percentage().await {
+                            percentage_prop.lock().await.set(percentage / 100.0).unwrap();
+                        }
+                        if let Ok(state) = apc.state().await {
+                            // `apc.state()` already folds apcupsd's `ONLINE` status into
+                            // `Charging`/`FullyCharged` by `BCHARGE`, so this matches the
+                            // same way the UPower/sysfs path derives `charging` from `State`.
+                            charging_prop
+                                .lock()
+                                .await
+                                .set(matches!(state, upower::device::State::Charging))
+                                .unwrap();
+                            let time_to_empty = apc.time_to_empty().await.unwrap_or(Duration::ZERO);
+                            time_to
+                                .lock()
+                                .await
+                                .set((state, time_to_empty.as_secs(), 0))
+                                .unwrap();
+                        }
+                        // apcupsd reports instantaneous charge, not design capacity, so it
+                        // never has a `health` reading; leave the property at its default.
+                        if let Ok(health) = apc.health().await {
+                            health_prop.lock().await.set(health).unwrap();
+                        }
+                    }
+                    tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                }
+            });
+            continue;
+        }
+
+        if activity_config.backend == BackendKind::UPower && module.connection.is_none() {
+            log::warn!("backend forced to UPower for activity {} but the system D-Bus isn't reachable", activity_id);
+            continue;
+        }
+        let use_upower = module.connection.is_some() && activity_config.backend != BackendKind::Sysfs;
+        if !use_upower {
+            // no UPower connection (or the config forced sysfs): poll
+            // `/sys/class/power_supply` directly instead, there's no change-notification
+            // mechanism there so this has to be a plain interval.
+            let battery_name = activity_config.battery.clone();
+            let active = module.session_monitor.receiver();
+            rt.handle().spawn(async move {
+                let name = if battery_name.is_empty() {
+                    SysfsBattery::enumerate().ok().and_then(|n| n.into_iter().next())
+                } else {
+                    Some(battery_name)
+                };
+                let Some(name) = name else {
+                    log::warn!("no sysfs battery found for activity {}", activity_id);
+                    return;
+                };
+                let device = SysfsBattery::new(&name);
+                let mut was_available = false;
+                loop {
+                    if !*active.borrow() {
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                        continue;
+                    }
+                    let available = device.is_available().await;
+                    if available != was_available {
+                        register_tx.send((activity_id.clone(), available)).unwrap();
+                        was_available = available;
+                    }
+                    if available {
+                        if let Ok(percentage) = device.percentage().await {
+                            percentage_prop.lock().await.set(percentage / 100.0).unwrap();
+                        }
+                        if let Ok(state) = device.state().await {
+                            charging_prop
+                                .lock()
+                                .await
+                                .set(matches!(state, upower::device::State::Charging))
+                                .unwrap();
+                            let time_to_full = device.time_to_full().await.unwrap_or(Duration::ZERO);
+                            let time_to_empty = device.time_to_empty().await.unwrap_or(Duration::ZERO);
+                            time_to
+                                .lock()
+                                .await
+                                .set((state, time_to_empty.as_secs(), time_to_full.as_secs()))
+                                .unwrap();
+                        }
+                        if let Ok(health) = device.health().await {
+                            health_prop.lock().await.set(health).unwrap();
+                        }
+                    }
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                }
+            });
+            continue;
+        }
 
         let mut device_rx = device_change_updater(
             &rt,
             activity_id.clone(),
-            &module.connection.clone(),
+            &module.connection.clone().unwrap(),
             !config.hide_if_missing,
             register_tx.clone(),
             &activity_config.battery,
         );
 
         rt.handle().spawn({
-            let conn = module.connection.clone();
+            let conn = module.connection.clone().unwrap();
+            let mut active = module.session_monitor.receiver();
             async move {
             loop{
                 let device = match device_rx.recv().await {
@@ -347,26 +592,10 @@ fn producer(module: &PowerModule) {
                     }
                 };
                 let device_obj=Device::from_path(&conn, dev_path).await.unwrap();
-                let result: anyhow::Result<()> = async {
-                    loop {
-                        // log::debug!("looping");
-                        if !device_rx.is_empty(){
-                            return Ok(());
-                        }
-                        // if config.battery.is_empty() {
-                        //     // use display and drop device_rx
-                        //     // let dev_type=display.type_().await.unwrap();
-                        //     // let level = display.battery_level().await.unwrap();
-                        //     // let state = display.state().await.unwrap();
-                        //     // let tech = display.technology().await.unwrap();
-                        //     // let percentage = display.proxy.percentage().await.unwrap();
-                        //     // log::debug!("Display: \nBattery level: {:?}, \ntype: {:?}, \nstate: {:?}, \ntechnology: {:?}, \npercentage: {:?}", level, dev_type, state, tech, percentage);
-
-                        //     // tokio::time::sleep(std::time::Duration::from_millis(3500)).await;
-                        //     continue;
-                        // }
-                        // TODO keep the device enumeration and update the state if a device was added/removed
-
+                // reads every property this activity cares about and, if it's the configured
+                // battery, pushes them onto the dynamic properties
+                macro_rules! refresh {
+                    () => {{
                         let name = device_obj.proxy.native_path().await.with_context(||"getting name")?;
                         let dev_type=device_obj.type_().await.with_context(||"getting dev_type")?;
                         let level = device_obj.battery_level().await.with_context(||"getting level")?;
@@ -375,7 +604,7 @@ fn producer(module: &PowerModule) {
                         let percentage = device_obj.proxy.percentage().await.with_context(||"getting percentage")?;
                         let time_to_full = device_obj.time_to_full().await.unwrap_or(Duration::ZERO);
                         let time_to_empty = device_obj.time_to_empty().await.unwrap_or(Duration::ZERO);
-                        let hist = if device_obj.proxy.has_history().await.with_context(||"getting has_history")?{
+                        let mut hist = if device_obj.proxy.has_history().await.with_context(||"getting has_history")?{
                             device_obj.get_history(upower::device::HistoryType::Charge, config.max_duration_secs as u32, config.max_duration_secs/60).await.with_context(||"getting history(charge)")?
                         }else{
                             Vec::new()
@@ -386,16 +615,75 @@ fn producer(module: &PowerModule) {
                         } else{
                             Vec::new()
                         };
+                        // `rate` is its own independent series (different timestamps/resolution
+                        // than `hist`), so join it in by nearest timestamp rather than discarding it.
+                        upower::device::merge_power_draw(&mut hist, &rate);
+                        let health = device_obj.health().await.ok();
                         if name == config.battery{
                             // log::debug!("Found device: {:?}, hist: {:#?}, rate: {:#?}", name, hist, rate);
                             percentage_prop.lock().await.set(percentage/100.0).unwrap();
                             charging_prop.lock().await.set(matches!(state,upower::device::State::Charging)).unwrap();
                             time_to.lock().await.set((state, time_to_empty.as_secs(), time_to_full.as_secs())).unwrap();
                             points.lock().await.set(hist).unwrap();
+                            if let Some(health) = health {
+                                health_prop.lock().await.set(health).unwrap();
+                            }
                         }
                         log::debug!("Name: {name} \nBattery level: {:?}, \ntype: {:?}, \nstate: {:?}, \ntechnology: {:?}, \npercentage: {:?}", level, dev_type, state, tech, percentage);
+                    }};
+                }
+                let result: anyhow::Result<()> = async {
+                    // don't bother reading properties/history while the session is
+                    // locked/suspending, nobody can see the result anyway
+                    while !*active.borrow() {
+                        if active.changed().await.is_err() {
+                            return Ok(());
+                        }
+                    }
+                    // read once immediately so the activity doesn't sit blank until the first
+                    // property-changed signal or the fallback timer fires
+                    refresh!();
 
-                        tokio::time::sleep(std::time::Duration::from_millis(3500)).await;
+                    let mut changes = device_obj.changes().await.with_context(||"watching property changes")?;
+                    // history points aren't covered by receive_*_changed, so they're refreshed on
+                    // a long timer instead of every property tick
+                    let mut history_refresh = tokio::time::interval(std::time::Duration::from_secs(30));
+                    history_refresh.tick().await; // the first tick fires immediately
+                    let mut monitor_alive = true;
+
+                    loop {
+                        if !device_rx.is_empty(){
+                            return Ok(());
+                        }
+                        tokio::select! {
+                            change = changes.next() => {
+                                if change.is_none(){
+                                    return Ok(());
+                                }
+                                if *active.borrow() {
+                                    refresh!();
+                                }
+                            }
+                            _ = history_refresh.tick() => {
+                                if *active.borrow() {
+                                    refresh!();
+                                }
+                            }
+                            changed = active.changed(), if monitor_alive => {
+                                // the monitor's sender is gone (logind unavailable or stopped):
+                                // its last reported state stays in effect, stop watching it
+                                if changed.is_err() {
+                                    monitor_alive = false;
+                                    continue;
+                                }
+                                // session just (un)locked or finished suspending: if it just
+                                // became active again, force an immediate refresh so the activity
+                                // doesn't show a stale state
+                                if *active.borrow() {
+                                    refresh!();
+                                }
+                            }
+                        }
                     }
                 }.await;
                 log::debug!("Device updater for {} returned in loop: {result:#?}", activity_id);
@@ -428,6 +716,17 @@ pub fn device_change_updater(
         }
         let mut old_device = Option::None;
         let mut found_device = Option::None;
+
+        // react to the UPower manager's DeviceAdded/DeviceRemoved signals rather than
+        // re-enumerating on a fixed poll; keep a long fallback timer in case a signal is missed
+        // (e.g. the device appeared before we subscribed).
+        let mut device_added = pw.receive_device_added().await.unwrap();
+        let mut device_removed = pw.receive_device_removed().await.unwrap();
+        let mut fallback_refresh = tokio::time::interval(std::time::Duration::from_secs(30));
+        // companion backend for devices UPower is slow to notice (hot-plugged USB HID battery
+        // packs); `None` just means this arm never fires
+        let mut udev_events = crate::udev_watch::spawn_watcher();
+
         tokio::select! {
             clean = cleanup_tx.recv() => {
                 if let Ok(sender)= clean {
@@ -480,7 +779,17 @@ pub fn device_change_updater(
                         }
                         register_tx.send((activity_id.clone(), keep_registered)).unwrap();
                     }
-                    tokio::time::sleep(std::time::Duration::from_millis(4000)).await;
+                    tokio::select! {
+                        _ = device_added.next() => {}
+                        _ = device_removed.next() => {}
+                        _ = fallback_refresh.tick() => {}
+                        _ = async {
+                            match udev_events.as_mut() {
+                                Some(rx) => { rx.recv().await; }
+                                None => std::future::pending::<()>().await,
+                            }
+                        } => {}
+                    }
                 }
             } => {
                 log::warn!("Device change updater for {}(device_name: {}) stopped", activity_id, device_name);
@@ -0,0 +1,146 @@
+//! Continuous sampling of a [`Device`] into a stream of serializable [`HistoryEntry`] rows, so a
+//! discharge/charge curve can be captured to disk for later plotting/analysis.
+//!
+//! [`Device`]: crate::upower::device::Device
+
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::upower::device::{Device, HistoryEntry, State};
+
+/// Decides when a [`Recorder::run`] loop should stop, given the samples collected so far.
+pub trait StopSignal {
+    fn should_stop(&mut self, samples: &[HistoryEntry]) -> bool;
+}
+
+/// Stops once `duration` has elapsed since the first sample was collected.
+pub struct AfterDuration {
+    duration: Duration,
+    start: Option<Instant>,
+}
+
+impl AfterDuration {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            start: None,
+        }
+    }
+}
+
+impl StopSignal for AfterDuration {
+    fn should_stop(&mut self, samples: &[HistoryEntry]) -> bool {
+        if samples.is_empty() {
+            return false;
+        }
+        let start = *self.start.get_or_insert_with(Instant::now);
+        start.elapsed() >= self.duration
+    }
+}
+
+/// Stops once `count` rows have been collected.
+pub struct AfterCount(pub usize);
+
+impl StopSignal for AfterCount {
+    fn should_stop(&mut self, samples: &[HistoryEntry]) -> bool {
+        samples.len() >= self.0
+    }
+}
+
+/// Stops once the most recent sample reports the battery as [`State::FullyCharged`].
+pub struct UntilFullyCharged;
+
+impl StopSignal for UntilFullyCharged {
+    fn should_stop(&mut self, samples: &[HistoryEntry]) -> bool {
+        matches!(samples.last(), Some(entry) if matches!(entry.state, State::FullyCharged))
+    }
+}
+
+/// Samples a [`Device`] at a fixed interval, handing each row to a caller-supplied sink until
+/// `stop` fires.
+pub struct Recorder {
+    interval: Duration,
+}
+
+impl Recorder {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+
+    /// Samples `device`'s percentage/state/power draw every `interval`, calling `emit` for each
+    /// row, until `stop` says to halt. Returns every row collected.
+    pub async fn run(
+        &self,
+        device: &Device,
+        mut stop: impl StopSignal,
+        mut emit: impl FnMut(&HistoryEntry),
+    ) -> zbus::Result<Vec<HistoryEntry>> {
+        let mut samples = Vec::new();
+        loop {
+            let percentage = device.proxy.percentage().await?;
+            let state = device.state().await?;
+            let power_draw = device.proxy.energy_rate().await.ok();
+            let health = match (
+                device.proxy.energy_full().await,
+                device.proxy.energy_full_design().await,
+            ) {
+                (Ok(full), Ok(design)) if design > 0.0 => Some(full / design * 100.0),
+                _ => None,
+            };
+            let timestamp = chrono::Local::now().timestamp() as u32;
+            let entry = HistoryEntry {
+                timestamp,
+                value: percentage,
+                state,
+                power_draw,
+                health,
+            };
+            emit(&entry);
+            samples.push(entry);
+            if stop.should_stop(&samples) {
+                break;
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+        Ok(samples)
+    }
+}
+
+/// Writes `entries` as CSV rows (`timestamp,value,state,power_draw,health`) to `writer`.
+pub async fn write_csv<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    entries: &[HistoryEntry],
+) -> std::io::Result<()> {
+    writer
+        .write_all(b"timestamp,value,state,power_draw,health\n")
+        .await?;
+    for entry in entries {
+        let power_draw = entry
+            .power_draw
+            .map_or(String::new(), |watts| watts.to_string());
+        let health = entry
+            .health
+            .map_or(String::new(), |percent| percent.to_string());
+        let line = format!(
+            "{},{},{:?},{},{}\n",
+            entry.timestamp, entry.value, entry.state, power_draw, health
+        );
+        writer.write_all(line.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Writes `entries` as newline-delimited JSON to `writer`.
+pub async fn write_json_lines<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    entries: &[HistoryEntry],
+) -> std::io::Result<()> {
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        writer.write_all(line.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Optional udev-based companion to UPower device discovery.
+//!
+//! UPower can be slow to report hot-plugged USB HID battery packs, but a fresh `power_supply`/
+//! `hid` uevent is immediate, so subscribing to udev lets callers trigger a re-enumeration as
+//! soon as the kernel sees the device rather than waiting for the next UPower signal/poll.
+
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Spawns a blocking thread that listens for `power_supply`/`hid` udev events and forwards a
+/// notification for each one.
+///
+/// Returns `None` (after logging a warning) if a udev monitor socket can't be opened, so callers
+/// can fall back to relying on UPower's own signals/poll alone.
+pub fn spawn_watcher() -> Option<UnboundedReceiver<()>> {
+    let socket = (|| -> std::io::Result<udev::MonitorSocket> {
+        udev::MonitorBuilder::new()?
+            .match_subsystem("power_supply")?
+            .match_subsystem("hid")?
+            .listen()
+    })();
+    let socket = match socket {
+        Ok(socket) => socket,
+        Err(err) => {
+            dynisland_core::abi::log::warn!(
+                "udev monitor unavailable, hot-plug detection for HID battery packs is disabled: {}",
+                err
+            );
+            return None;
+        }
+    };
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        for _event in socket.iter() {
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    Some(rx)
+}
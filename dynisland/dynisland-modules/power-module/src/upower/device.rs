@@ -3,10 +3,16 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+use futures::{Stream, StreamExt};
+use serde::Serialize;
 use zbus::zvariant::ObjectPath;
 
 use super::proxy::device::DeviceProxy;
 
+/// a UPower device's D-Bus object path (e.g. `/org/freedesktop/UPower/devices/battery_BAT0`),
+/// used as the stable key to tell devices apart in multi-device widgets
+pub type DeviceId = String;
+
 #[derive(Debug, Clone, Copy)]
 pub enum HistoryType {
     Rate,
@@ -38,7 +44,7 @@ impl Display for StatisticsType {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum State {
     Unknown = 0,
     Charging = 1,
@@ -64,21 +70,40 @@ impl From<u32> for State {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct HistoryEntry {
     pub timestamp: u32,
     pub value: f64,
     pub state: State,
+    /// instantaneous power draw in watts, when sampled alongside the percentage history
+    pub power_draw: Option<f64>,
+    /// capacity health (`energy_full` / `energy_full_design`) as a percentage, when sampled
+    /// alongside the percentage history
+    pub health: Option<f64>,
 }
 
-#[derive(Debug, Clone)]
+impl HistoryEntry {
+    /// `power_draw`, signed by `state` (negative while discharging, positive while charging,
+    /// `0.0` when unknown or not sampled), the convention every widget that surfaces an
+    /// instantaneous watts reading uses.
+    pub fn signed_power_draw(&self) -> f64 {
+        let rate = self.power_draw.unwrap_or(0.0);
+        match self.state {
+            State::Discharging => -rate,
+            State::Charging => rate,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 #[allow(dead_code)]
 pub struct StatisticsEntry {
     pub value: f64,
     pub accuracy: f64,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub enum BatteryLevel {
     Unknown = 0,
     None = 1,
@@ -108,7 +133,7 @@ impl From<u32> for BatteryLevel {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub enum Technology {
     Unknown = 0,
     LithiumIon = 1,
@@ -273,12 +298,19 @@ impl Device {
     ) -> zbus::Result<Vec<HistoryEntry>> {
         let type_ = type_.to_string();
         let entries = self.proxy.get_history(&type_, timespan, resolution).await?;
+        // UPower only exposes the *current* energy_full/energy_full_design ratio, not a
+        // historical series, but capacity degrades slowly enough relative to a typical
+        // `max_duration_secs` window that stamping every entry with the current reading is a
+        // reasonable approximation instead of leaving the whole series `None`.
+        let health = self.health().await.ok();
         let mut result = Vec::new();
         for (timestamp, value, state) in entries {
             result.push(HistoryEntry {
                 timestamp,
                 value,
                 state: State::from(state),
+                power_draw: None,
+                health,
             });
         }
         // reverse the list so that the oldest entry is first
@@ -333,6 +365,20 @@ impl Device {
         Ok(DeviceType::from(type_))
     }
 
+    /// Capacity health as a percentage (`energy_full` / `energy_full_design`), i.e. how much of
+    /// the battery's original design capacity it can still hold. Same computation
+    /// [`crate::recorder::Recorder::run`] uses when sampling a row for the offline exporter.
+    pub async fn health(&self) -> zbus::Result<f64> {
+        let full = self.proxy.energy_full().await?;
+        let design = self.proxy.energy_full_design().await?;
+        if design <= 0.0 {
+            return Err(zbus::Error::Failure(
+                "energy_full_design is zero".to_string(),
+            ));
+        }
+        Ok(full / design * 100.0)
+    }
+
     #[allow(dead_code)]
     pub async fn update_time(&self) -> zbus::Result<SystemTime> {
         let time = self.proxy.update_time().await?;
@@ -350,3 +396,185 @@ impl Device {
         Ok(WarningLevel::from(level))
     }
 }
+
+/// A decoded, typed change event for one of [`Device`]'s watched properties.
+#[derive(Debug, Clone, Copy)]
+pub enum DeviceChange {
+    State(State),
+    WarningLevel(WarningLevel),
+    BatteryLevel(BatteryLevel),
+}
+
+impl Device {
+    /// Returns a live [`Stream`] of decoded property-change events, built on top of
+    /// `DeviceProxy`'s `receive_*_changed` property-change signals, so callers can react to a
+    /// state/warning/battery-level change without polling.
+    ///
+    /// Bursts of rapid updates are coalesced (see [`coalesce`]) so a consumer only sees the most
+    /// recent value for a short window of activity, instead of every intermediate signal.
+    pub async fn changes(&self) -> zbus::Result<impl Stream<Item = DeviceChange> + '_> {
+        let state = self
+            .proxy
+            .receive_state_changed()
+            .await
+            .filter_map(|c| async move {
+                c.get()
+                    .await
+                    .ok()
+                    .map(|v| DeviceChange::State(State::from(v)))
+            });
+        let warning = self
+            .proxy
+            .receive_warning_level_changed()
+            .await
+            .filter_map(|c| async move {
+                c.get()
+                    .await
+                    .ok()
+                    .map(|v| DeviceChange::WarningLevel(WarningLevel::from(v)))
+            });
+        let battery = self
+            .proxy
+            .receive_battery_level_changed()
+            .await
+            .filter_map(|c| async move {
+                c.get()
+                    .await
+                    .ok()
+                    .map(|v| DeviceChange::BatteryLevel(BatteryLevel::from(v)))
+            });
+        let merged = futures::stream::select(futures::stream::select(state, warning), battery);
+        Ok(coalesce(merged, Duration::from_millis(150)))
+    }
+
+    /// Live stream of `state` changes, decoded into [`State`] and coalesced.
+    pub async fn watch_state(&self) -> zbus::Result<impl Stream<Item = State> + '_> {
+        let stream = self
+            .proxy
+            .receive_state_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(State::from) });
+        Ok(coalesce(stream, Duration::from_millis(150)))
+    }
+
+    /// Live stream of `warning_level` changes, decoded into [`WarningLevel`] and coalesced.
+    #[allow(dead_code)]
+    pub async fn watch_warning_level(&self) -> zbus::Result<impl Stream<Item = WarningLevel> + '_> {
+        let stream = self
+            .proxy
+            .receive_warning_level_changed()
+            .await
+            .filter_map(|c| async move { c.get().await.ok().map(WarningLevel::from) });
+        Ok(coalesce(stream, Duration::from_millis(150)))
+    }
+}
+
+impl Device {
+    /// Derives a time-to-empty estimate from the trailing run of `HistoryType::Charge` samples
+    /// recorded while discharging, fitting a least-squares line and extrapolating to zero percent.
+    /// This smooths over the `0` that [`Device::time_to_empty`] frequently reports right after a
+    /// state change, similarly to how i3status/zedmon stabilize their readings.
+    #[allow(dead_code)]
+    pub async fn estimate_time_to_empty(&self) -> zbus::Result<Option<Duration>> {
+        let history = self.get_history(HistoryType::Charge, 3 * 3600, 300).await?;
+        let run = trailing_run_matching(&history, |s| matches!(s, State::Discharging));
+        let Some(slope) = least_squares_slope(&run) else {
+            return Ok(None);
+        };
+        if slope >= 0.0 {
+            return Ok(None);
+        }
+        let current = run.last().map(|(_, v)| *v).unwrap_or(0.0);
+        Ok(Some(Duration::from_secs_f64((current / -slope).max(0.0))))
+    }
+
+    /// Derives a time-to-full estimate the same way as [`Device::estimate_time_to_empty`], fitting
+    /// the trailing run of samples recorded while charging and extrapolating to 100%.
+    #[allow(dead_code)]
+    pub async fn estimate_time_to_full(&self) -> zbus::Result<Option<Duration>> {
+        let history = self.get_history(HistoryType::Charge, 3 * 3600, 300).await?;
+        let run = trailing_run_matching(&history, |s| matches!(s, State::Charging));
+        let Some(slope) = least_squares_slope(&run) else {
+            return Ok(None);
+        };
+        if slope <= 0.0 {
+            return Ok(None);
+        }
+        let current = run.last().map(|(_, v)| *v).unwrap_or(0.0);
+        Ok(Some(Duration::from_secs_f64(
+            ((100.0 - current) / slope).max(0.0),
+        )))
+    }
+}
+
+/// Stamps each entry in `charge` with the `value` of its nearest-by-timestamp entry in `rate`,
+/// as `power_draw`. `HistoryType::Charge` and `HistoryType::Rate` are independent series from
+/// UPower with their own timestamps and resolutions, so there's no exact 1:1 join between them.
+pub fn merge_power_draw(charge: &mut [HistoryEntry], rate: &[HistoryEntry]) {
+    if rate.is_empty() {
+        return;
+    }
+    for entry in charge.iter_mut() {
+        let nearest = rate
+            .iter()
+            .min_by_key(|r| (r.timestamp as i64 - entry.timestamp as i64).abs());
+        entry.power_draw = nearest.map(|r| r.value);
+    }
+}
+
+/// Returns the trailing run of `history` (oldest-first) whose `state` satisfies `matches`, i.e.
+/// the most recent unbroken stretch of samples recorded in the same charge/discharge state.
+fn trailing_run_matching(
+    history: &[HistoryEntry],
+    matches: impl Fn(State) -> bool,
+) -> Vec<(f64, f64)> {
+    let mut run = Vec::new();
+    for entry in history.iter().rev() {
+        if matches(entry.state) {
+            run.push((entry.timestamp as f64, entry.value));
+        } else {
+            break;
+        }
+    }
+    run.reverse();
+    run
+}
+
+/// Fits `y = m*x + b` to `points` and returns the slope `m`, or `None` if there are fewer than 3
+/// points or they don't vary in `x` (a flat/degenerate fit).
+fn least_squares_slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 3 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_v = points.iter().map(|(_, v)| v).sum::<f64>() / n;
+    let mut num = 0.0;
+    let mut den = 0.0;
+    for (t, v) in points {
+        num += (t - mean_t) * (v - mean_v);
+        den += (t - mean_t) * (t - mean_t);
+    }
+    if den == 0.0 {
+        return None;
+    }
+    Some(num / den)
+}
+
+/// Collapses a burst of rapidly-arriving items into the last one observed within `window` of
+/// inactivity, so a consumer doesn't have to redraw on every intermediate signal.
+fn coalesce<S>(stream: S, window: Duration) -> impl Stream<Item = S::Item>
+where
+    S: Stream + Unpin,
+{
+    futures::stream::unfold(stream, move |mut stream| async move {
+        let mut item = stream.next().await?;
+        loop {
+            match tokio::time::timeout(window, stream.next()).await {
+                Ok(Some(next)) => item = next,
+                Ok(None) | Err(_) => break,
+            }
+        }
+        Some((item, stream))
+    })
+}
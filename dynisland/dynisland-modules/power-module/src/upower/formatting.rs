@@ -0,0 +1,94 @@
+//! Presentation helpers for turning a [`Device`]'s properties into user-facing text: a small
+//! placeholder-based [`FormatTemplate`] and an icon ramp keyed by [`BatteryLevel`]/[`State`].
+
+use std::time::Duration;
+
+use super::device::{BatteryLevel, Device, State};
+
+/// A charging/discharging icon ramp, indexed by battery level.
+///
+/// Callers register their own glyphs (e.g. nerd-font icons, plain text, unicode blocks) so the
+/// same [`Device`] can drive a status bar, a notification, or a CLI readout without reimplementing
+/// the level-to-icon mapping each time.
+#[derive(Debug, Clone)]
+pub struct IconSet {
+    /// Icons shown while charging, ordered `[none, low, critical, normal, high, full]`.
+    pub charging: [String; 6],
+    /// Icons shown while discharging (or in any other non-charging state), same ordering.
+    pub discharging: [String; 6],
+}
+
+impl IconSet {
+    /// Returns the icon for `level`/`state` from this set.
+    pub fn icon_for(&self, level: BatteryLevel, state: State) -> &str {
+        let idx = level_index(level);
+        let ramp = if matches!(state, State::Charging | State::PendingCharge) {
+            &self.charging
+        } else {
+            &self.discharging
+        };
+        &ramp[idx]
+    }
+}
+
+fn level_index(level: BatteryLevel) -> usize {
+    match level {
+        BatteryLevel::Unknown | BatteryLevel::None => 0,
+        BatteryLevel::Low => 1,
+        BatteryLevel::Critical => 2,
+        BatteryLevel::Unknown2 | BatteryLevel::Unknown5 | BatteryLevel::Normal => 3,
+        BatteryLevel::High => 4,
+        BatteryLevel::Full => 5,
+    }
+}
+
+/// Maps `level`/`state` to an icon from `icons`.
+pub fn battery_level_to_icon(level: BatteryLevel, state: State, icons: &IconSet) -> String {
+    icons.icon_for(level, state).to_string()
+}
+
+/// Formats a [`Duration`] as `H:MM`, falling back to `"--:--"` when UPower hasn't produced a
+/// usable estimate yet (i.e. the duration is zero).
+pub fn format_duration(time: Duration) -> String {
+    if time.is_zero() {
+        return "--:--".to_string();
+    }
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 3600, (secs % 3600) / 60)
+}
+
+/// A small placeholder-based template, expanded against a [`Device`]'s live properties.
+///
+/// Supported placeholders: `{percentage}`, `{time}`, `{state}`, `{technology}`, `{icon}`.
+#[derive(Debug, Clone)]
+pub struct FormatTemplate(String);
+
+impl FormatTemplate {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(template.into())
+    }
+
+    /// Reads `device`'s current properties and expands this template's placeholders against
+    /// them, using `icons` to resolve `{icon}`.
+    pub async fn expand(&self, device: &Device, icons: &IconSet) -> zbus::Result<String> {
+        let percentage = device.proxy.percentage().await?;
+        let state = device.state().await?;
+        let technology = device.technology().await?;
+        let level = device.battery_level().await?;
+        let time = match state {
+            State::Charging | State::PendingCharge => {
+                device.time_to_full().await.unwrap_or(Duration::ZERO)
+            }
+            _ => device.time_to_empty().await.unwrap_or(Duration::ZERO),
+        };
+
+        let expanded = self
+            .0
+            .replace("{percentage}", &format!("{:.0}%", percentage))
+            .replace("{time}", &format_duration(time))
+            .replace("{state}", &format!("{:?}", state))
+            .replace("{technology}", &format!("{:?}", technology))
+            .replace("{icon}", icons.icon_for(level, state));
+        Ok(expanded)
+    }
+}
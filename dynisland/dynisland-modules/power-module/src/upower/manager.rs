@@ -0,0 +1,78 @@
+//! A manager type wrapping the `org.freedesktop.UPower` root object, so callers don't need to
+//! already know a device's D-Bus object path to find it.
+
+use futures::{Stream, StreamExt};
+use zbus::zvariant::OwnedObjectPath;
+
+use super::{
+    device::{Device, DeviceType},
+    proxy::upower::UPowerProxy,
+};
+
+/// Enumerates and filters the devices known to the system's UPower daemon.
+#[derive(Debug, Clone)]
+pub struct UPower {
+    proxy: UPowerProxy<'static>,
+    connection: zbus::Connection,
+}
+
+impl UPower {
+    /// Connect to the `org.freedesktop.UPower` root object on `conn`.
+    pub async fn new(conn: &zbus::Connection) -> zbus::Result<Self> {
+        let proxy = UPowerProxy::new(conn).await?;
+        Ok(Self {
+            proxy,
+            connection: conn.clone(),
+        })
+    }
+
+    /// Returns every device currently registered with UPower.
+    pub async fn enumerate_devices(&self) -> zbus::Result<Vec<Device>> {
+        let paths = self.proxy.enumerate_devices().await?;
+        let mut devices = Vec::with_capacity(paths.len());
+        for path in paths {
+            devices.push(Device::from_path(&self.connection, path).await?);
+        }
+        Ok(devices)
+    }
+
+    /// Returns the composite "display device" UPower uses to represent the overall power state.
+    pub async fn display_device(&self) -> zbus::Result<Device> {
+        let path = self.proxy.get_display_device().await?;
+        Device::from_path(&self.connection, path).await
+    }
+
+    /// Returns every known device whose `type_` matches `device_type`.
+    #[allow(dead_code)]
+    pub async fn devices_of_type(&self, device_type: DeviceType) -> zbus::Result<Vec<Device>> {
+        let mut matching = Vec::new();
+        for device in self.enumerate_devices().await? {
+            if device.type_().await? as u32 == device_type as u32 {
+                matching.push(device);
+            }
+        }
+        Ok(matching)
+    }
+
+    /// Stream of object paths for devices added to UPower after this call.
+    #[allow(dead_code)]
+    pub async fn on_device_added(&self) -> zbus::Result<impl Stream<Item = OwnedObjectPath> + '_> {
+        Ok(self
+            .proxy
+            .receive_device_added()
+            .await?
+            .filter_map(|signal| async move { signal.args().ok().map(|args| args.device) }))
+    }
+
+    /// Stream of object paths for devices removed from UPower after this call.
+    #[allow(dead_code)]
+    pub async fn on_device_removed(
+        &self,
+    ) -> zbus::Result<impl Stream<Item = OwnedObjectPath> + '_> {
+        Ok(self
+            .proxy
+            .receive_device_removed()
+            .await?
+            .filter_map(|signal| async move { signal.args().ok().map(|args| args.device) }))
+    }
+}
@@ -0,0 +1,4 @@
+pub mod device;
+pub mod formatting;
+pub mod manager;
+pub mod proxy;
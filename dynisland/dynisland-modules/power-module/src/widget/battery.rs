@@ -1,4 +1,7 @@
-use std::{cell::RefCell, f64::consts::PI};
+use std::{
+    cell::{Cell, RefCell},
+    f64::consts::PI,
+};
 
 use dyn_fmt::AsStrFormatExt;
 use dynisland_core::abi::{gdk, glib, gtk, log};
@@ -6,7 +9,7 @@ use gdk::RGBA;
 use glib::{
     subclass::{
         object::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
-        types::{ObjectSubclass, ObjectSubclassExt},
+        types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
     },
     Object, Properties,
 };
@@ -17,6 +20,33 @@ use gtk::{
     BinLayout,
 };
 
+/// How the charge level is painted inside the battery outline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillStyle {
+    #[default]
+    Solid,
+    Segments,
+}
+
+impl FillStyle {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "solid" => Some(Self::Solid),
+            "segments" => Some(Self::Segments),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FillStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Solid => write!(f, "solid"),
+            Self::Segments => write!(f, "segments"),
+        }
+    }
+}
+
 glib::wrapper! {
     pub struct Battery(ObjectSubclass<BatteryPriv>)
     @extends gtk::Widget;
@@ -37,6 +67,20 @@ pub struct BatteryPriv {
     #[property(get, set)]
     #[doc = "Pango markup for the percentage text, the percentage will be inserted in the first `{}` placeholder"]
     percentage_markup: RefCell<String>,
+    /// `"solid"` for a continuous fill or `"segments"` for discrete cells, see [`FillStyle`]
+    #[property(get, set, type=String)]
+    fill_style: RefCell<FillStyle>,
+    /// number of discrete cells drawn when `fill_style` is `"segments"`
+    #[property(get, set)]
+    segment_count: Cell<u32>,
+    /// draw an animated highlight wave over the fill while `charging` is true
+    #[property(get, set)]
+    animate_charging: Cell<bool>,
+
+    /// monotonic animation phase advanced by the frame clock tick callback; the charge wave
+    /// sweeps the lit region once per `1.0` of it
+    phase: RefCell<f64>,
+    tick_callback_id: RefCell<Option<gtk::TickCallbackId>>,
 }
 
 #[glib::object_subclass]
@@ -70,6 +114,11 @@ impl Default for BatteryPriv {
                     .to_string(),
             ),
             charging: RefCell::new(false),
+            fill_style: RefCell::new(FillStyle::default()),
+            segment_count: Cell::new(10),
+            animate_charging: Cell::new(false),
+            phase: RefCell::new(0.0),
+            tick_callback_id: RefCell::new(None),
         }
     }
 }
@@ -78,14 +127,10 @@ impl Default for BatteryPriv {
 impl ObjectImpl for BatteryPriv {
     fn constructed(&self) {
         self.parent_constructed();
-        // let battery = self.obj().clone();
-        // glib::timeout_add_local(Duration::from_millis(100), move || {
-        //     battery.queue_draw();
-        //     glib::ControlFlow::Continue
-        // });
     }
 
     fn dispose(&self) {
+        self.stop_animation();
         while let Some(child) = self.obj().first_child() {
             child.unparent();
         }
@@ -124,6 +169,11 @@ impl ObjectImpl for BatteryPriv {
             "charging" => {
                 let charging: bool = value.get().unwrap();
                 self.charging.replace(charging);
+                if charging && self.animate_charging.get() {
+                    self.start_animation();
+                } else {
+                    self.stop_animation();
+                }
                 self.obj().queue_draw();
             }
             "show-percentage" => {
@@ -131,6 +181,29 @@ impl ObjectImpl for BatteryPriv {
                 self.show_percentage.replace(show);
                 self.obj().queue_draw();
             }
+            "fill-style" => {
+                let name: String = value.get().unwrap();
+                if let Some(style) = FillStyle::parse(&name) {
+                    self.fill_style.replace(style);
+                    self.obj().queue_draw();
+                } else {
+                    log::warn!("invalid fill style: {name}");
+                }
+            }
+            "segment-count" => {
+                let count: u32 = value.get().unwrap();
+                self.segment_count.set(count.max(1));
+                self.obj().queue_draw();
+            }
+            "animate-charging" => {
+                let animate: bool = value.get().unwrap();
+                self.animate_charging.set(animate);
+                if animate && *self.charging.borrow() {
+                    self.start_animation();
+                } else {
+                    self.stop_animation();
+                }
+            }
             _ => {
                 log::warn!("Battery: invalid property received: {}", pspec.name());
             }
@@ -144,6 +217,9 @@ impl ObjectImpl for BatteryPriv {
             "percentage" => self.percentage.borrow().to_value(),
             "charging" => self.charging.borrow().to_value(),
             "show-percentage" => self.show_percentage.borrow().to_value(),
+            "fill-style" => self.fill_style.borrow().to_string().to_value(),
+            "segment-count" => self.segment_count.get().to_value(),
+            "animate-charging" => self.animate_charging.get().to_value(),
             _ => self.derived_property(id, pspec),
         }
     }
@@ -219,9 +295,19 @@ impl WidgetImpl for BatteryPriv {
                 let fill_color = self.fill_color.borrow();
 
                 // fill
-                ctx.rectangle(0.0, 0.0, main_w * percentage, req_h);
-                ctx.set_source_color(&fill_color);
-                ctx.fill().unwrap();
+                match *self.fill_style.borrow() {
+                    FillStyle::Solid => {
+                        ctx.rectangle(0.0, 0.0, main_w * percentage, req_h);
+                        ctx.set_source_color(&fill_color);
+                        ctx.fill().unwrap();
+                        if *self.charging.borrow() && self.animate_charging.get() {
+                            self.draw_charge_wave(&ctx, &fill_color, main_w * percentage, req_h);
+                        }
+                    }
+                    FillStyle::Segments => {
+                        self.draw_segments(&ctx, &fill_color, &bg_color, main_w, req_h, percentage);
+                    }
+                }
             }
             ctx.reset_clip();
 
@@ -259,6 +345,112 @@ impl WidgetImpl for BatteryPriv {
 }
 
 impl BatteryPriv {
+    /// starts the frame-clock tick callback that advances `phase` and repaints, a no-op if
+    /// already running
+    fn start_animation(&self) {
+        if self.tick_callback_id.borrow().is_some() {
+            return;
+        }
+        let id = self.obj().add_tick_callback(|widget, _clock| {
+            let imp = widget.imp();
+            let phase = *imp.phase.borrow() + 0.01;
+            imp.phase.replace(phase);
+            widget.queue_draw();
+            glib::ControlFlow::Continue
+        });
+        self.tick_callback_id.replace(Some(id));
+    }
+
+    /// removes the tick callback if one is running, to avoid leaking it once charging stops
+    /// or the widget is disposed
+    fn stop_animation(&self) {
+        if let Some(id) = self.tick_callback_id.take() {
+            id.remove();
+        }
+    }
+
+    /// draws a soft highlight band sweeping across the lit region, clipped to `0..lit_w`
+    fn draw_charge_wave(
+        &self,
+        ctx: &gtk::cairo::Context,
+        fill_color: &RGBA,
+        lit_w: f64,
+        req_h: f64,
+    ) {
+        if lit_w <= 0.0 {
+            return;
+        }
+        let phase = *self.phase.borrow() % 1.0;
+        let band_w = (lit_w * 0.3).max(8.0);
+        let x = phase * (lit_w + band_w) - band_w;
+
+        let gradient = gtk::cairo::LinearGradient::new(x, 0.0, x + band_w, 0.0);
+        let (r, g, b) = (
+            fill_color.red() as f64,
+            fill_color.green() as f64,
+            fill_color.blue() as f64,
+        );
+        gradient.add_color_stop_rgba(0.0, r, g, b, 0.0);
+        gradient.add_color_stop_rgba(0.5, 1.0, 1.0, 1.0, 0.35);
+        gradient.add_color_stop_rgba(1.0, r, g, b, 0.0);
+
+        ctx.rectangle(0.0, 0.0, lit_w, req_h);
+        ctx.clip();
+        ctx.set_source(&gradient).unwrap();
+        ctx.rectangle(0.0, 0.0, lit_w, req_h);
+        ctx.fill().unwrap();
+        ctx.reset_clip();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_segments(
+        &self,
+        ctx: &gtk::cairo::Context,
+        fill_color: &RGBA,
+        bg_color: &RGBA,
+        main_w: f64,
+        req_h: f64,
+        percentage: f64,
+    ) {
+        let segment_count = self.segment_count.get().max(1);
+        let lit = ((percentage * segment_count as f64).round() as u32).clamp(0, segment_count);
+
+        let gap = 2.0;
+        let segment_w = (main_w - gap * (segment_count - 1) as f64) / segment_count as f64;
+        let radius = (segment_w.min(req_h) * 0.2).min(4.0);
+        let dim_color = bg_color
+            .with_red(bg_color.red() * 0.3)
+            .with_green(bg_color.green() * 0.3)
+            .with_blue(bg_color.blue() * 0.3);
+
+        const DEG_0: f64 = 0.0;
+        const DEG_90: f64 = PI / 2.0;
+        const DEG_180: f64 = PI;
+        const DEG_270: f64 = PI * 3.0 / 2.0;
+        for i in 0..segment_count {
+            let x = i as f64 * (segment_w + gap);
+            ctx.new_sub_path();
+            ctx.move_to(x + radius, 0.0);
+            ctx.line_to(x + segment_w - radius, 0.0);
+            ctx.arc(x + segment_w - radius, radius, radius, DEG_270, DEG_0);
+            ctx.line_to(x + segment_w, req_h - radius);
+            ctx.arc(
+                x + segment_w - radius,
+                req_h - radius,
+                radius,
+                DEG_0,
+                DEG_90,
+            );
+            ctx.line_to(x + radius, req_h);
+            ctx.arc(x + radius, req_h - radius, radius, DEG_90, DEG_180);
+            ctx.line_to(x, radius);
+            ctx.arc(x + radius, radius, radius, DEG_180, DEG_270);
+            ctx.close_path();
+            ctx.set_source_color(if i < lit { fill_color } else { &dim_color });
+            ctx.fill().unwrap();
+        }
+    }
+
     fn draw_text(
         &self,
         ctx: &gtk::cairo::Context,
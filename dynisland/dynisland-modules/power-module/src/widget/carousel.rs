@@ -0,0 +1,333 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    time::Duration,
+};
+
+use dynisland_core::{
+    abi::{gdk, glib, gtk, log},
+    cast_dyn_any,
+    dynamic_activity::DynamicActivity,
+};
+use gdk::RGBA;
+use glib::{
+    prelude::ObjectExt,
+    subclass::{
+        object::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
+        types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+    },
+    Object, Properties,
+};
+use gtk::{
+    graphene::Rect,
+    prelude::*,
+    subclass::widget::{WidgetClassExt, WidgetImpl},
+    BinLayout, GestureClick,
+};
+
+use super::{
+    battery::Battery,
+    minimal::{color_for_stops, format_color_stops, parse_color_stops},
+};
+
+glib::wrapper! {
+    pub struct Carousel(ObjectSubclass<CarouselPriv>)
+    @extends gtk::Widget;
+}
+
+#[derive(Properties)]
+#[properties(wrapper_type = Carousel)]
+pub struct CarouselPriv {
+    #[property(get, set)]
+    pub low_battery_color: RefCell<String>,
+    #[property(get, set)]
+    pub charging_color: RefCell<String>,
+    #[property(get, set)]
+    pub normal_color: RefCell<String>,
+    /// index of the currently displayed battery in `order`
+    #[property(get, set)]
+    pub active_index: Cell<u32>,
+    /// milliseconds between automatic page advances, `0` disables auto-advance
+    #[property(get, set)]
+    pub auto_advance_interval_ms: Cell<u32>,
+    /// fraction (`0.0..=1.0`) below which a battery is considered low when `color_stops` is empty
+    #[property(get, set)]
+    pub low_battery_threshold: Cell<f64>,
+    /// `"pos:color,pos:color,..."` gradient stops the fill is interpolated across as it drains,
+    /// e.g. `"0:red,0.5:orange,1:green"`; falls back to a binary `low_battery_color`/`normal_color`
+    /// switch at `low_battery_threshold` when empty
+    #[property(get, set, type=String)]
+    pub color_stops: RefCell<Vec<(f64, RGBA)>>,
+
+    pub(super) batteries: RefCell<HashMap<String, Battery>>,
+    pub(super) order: RefCell<Vec<String>>,
+}
+
+impl Default for CarouselPriv {
+    fn default() -> Self {
+        Self {
+            low_battery_color: RefCell::new("red".to_string()),
+            charging_color: RefCell::new("green".to_string()),
+            normal_color: RefCell::new("white".to_string()),
+            active_index: Cell::new(0),
+            auto_advance_interval_ms: Cell::new(0),
+            low_battery_threshold: Cell::new(0.2),
+            color_stops: RefCell::new(Vec::new()),
+            batteries: RefCell::new(HashMap::new()),
+            order: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for CarouselPriv {
+    const NAME: &'static str = "PowerCarouselWidget";
+    type Type = Carousel;
+    type ParentType = gtk::Widget;
+
+    fn class_init(klass: &mut Self::Class) {
+        // if you use custom widgets from core you need to ensure the type
+        Battery::ensure_type();
+        klass.set_layout_manager_type::<BinLayout>();
+        klass.set_css_name("battery-carousel-widget");
+    }
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for CarouselPriv {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let gesture = GestureClick::new();
+        let carousel = self.obj().clone();
+        gesture.connect_pressed(move |_gesture, _n_press, _x, _y| {
+            carousel.next();
+        });
+        self.obj().add_controller(gesture);
+    }
+
+    fn dispose(&self) {
+        while let Some(child) = self.obj().first_child() {
+            child.unparent();
+        }
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "low-battery-color" => {
+                self.low_battery_color.replace(value.get().unwrap());
+            }
+            "charging-color" => {
+                self.charging_color.replace(value.get().unwrap());
+            }
+            "normal-color" => {
+                self.normal_color.replace(value.get().unwrap());
+            }
+            "active-index" => {
+                self.active_index.set(value.get().unwrap());
+            }
+            "auto-advance-interval-ms" => {
+                let interval: u32 = value.get().unwrap();
+                let previous = self.auto_advance_interval_ms.replace(interval);
+                if interval > 0 && interval != previous {
+                    // a timer started for an earlier interval stops itself on its next tick
+                    let carousel = self.obj().downgrade();
+                    glib::timeout_add_local(Duration::from_millis(interval as u64), move || {
+                        match carousel.upgrade() {
+                            Some(carousel) if carousel.imp().auto_advance_interval_ms.get() == interval => {
+                                carousel.next();
+                                glib::ControlFlow::Continue
+                            }
+                            _ => glib::ControlFlow::Break,
+                        }
+                    });
+                }
+            }
+            "low-battery-threshold" => {
+                self.low_battery_threshold.set(value.get().unwrap());
+            }
+            "color-stops" => {
+                let name: String = value.get().unwrap();
+                if let Some(stops) = parse_color_stops(&name) {
+                    self.color_stops.replace(stops);
+                } else {
+                    log::warn!("invalid color stops: {name}");
+                }
+            }
+            _ => {
+                log::warn!("Carousel: invalid property received: {}", pspec.name());
+            }
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "low-battery-color" => self.low_battery_color.borrow().to_value(),
+            "charging-color" => self.charging_color.borrow().to_value(),
+            "normal-color" => self.normal_color.borrow().to_value(),
+            "active-index" => self.active_index.get().to_value(),
+            "auto-advance-interval-ms" => self.auto_advance_interval_ms.get().to_value(),
+            "low-battery-threshold" => self.low_battery_threshold.get().to_value(),
+            "color-stops" => format_color_stops(&self.color_stops.borrow()).to_value(),
+            _ => {
+                log::warn!("Carousel: invalid property received: {}", pspec.name());
+                glib::Value::from_type(glib::Type::UNIT)
+            }
+        }
+    }
+}
+
+impl WidgetImpl for CarouselPriv {
+    fn snapshot(&self, snapshot: &gtk::Snapshot) {
+        let order = self.order.borrow();
+        if order.is_empty() {
+            return;
+        }
+        let active = (self.active_index.get() as usize).min(order.len() - 1);
+        if let Some(battery) = order
+            .get(active)
+            .and_then(|name| self.batteries.borrow().get(name).cloned())
+        {
+            self.obj().snapshot_child(&battery, snapshot);
+        }
+        self.draw_page_indicators(snapshot, order.len(), active);
+    }
+}
+
+impl CarouselPriv {
+    /// draws a small row of dots along the bottom edge, one per battery, with the active
+    /// one more opaque than the rest
+    fn draw_page_indicators(&self, snapshot: &gtk::Snapshot, count: usize, active: usize) {
+        if count < 2 {
+            return;
+        }
+        let (w, h) = (self.obj().width() as f64, self.obj().height() as f64);
+        let dot_radius = 2.5;
+        let gap = 6.0;
+        let total_w = count as f64 * gap;
+        let y = h - dot_radius - 2.0;
+        let rect = Rect::new(
+            0.0,
+            (y - dot_radius) as f32,
+            w as f32,
+            (dot_radius * 2.0 + 1.0) as f32,
+        );
+        let ctx = snapshot.append_cairo(&rect);
+        for i in 0..count {
+            let x = (w - total_w) / 2.0 + i as f64 * gap + gap / 2.0;
+            ctx.new_sub_path();
+            ctx.arc(x, y, dot_radius, 0.0, std::f64::consts::PI * 2.0);
+            let alpha: f32 = if i == active { 0.9 } else { 0.3 };
+            ctx.set_source_color(&RGBA::new(1.0, 1.0, 1.0, alpha));
+            ctx.fill().unwrap();
+        }
+        drop(ctx);
+    }
+}
+
+impl Carousel {
+    /// registered properties per battery name (e.g. `"BAT0"`):
+    /// * `percentage.<name>`: `f64`
+    /// * `charging.<name>`: `bool`
+    pub fn new(activity: &mut DynamicActivity, battery_names: &[String]) -> Self {
+        let this: Self = Object::builder().build();
+
+        for name in battery_names {
+            let battery = Battery::new();
+            battery.set_parent(&this);
+            this.imp()
+                .batteries
+                .borrow_mut()
+                .insert(name.clone(), battery);
+            this.imp().order.borrow_mut().push(name.clone());
+
+            let _ = activity.add_dynamic_property(format!("percentage.{name}"), 0.0_f64);
+            let carousel = this.clone();
+            let prop_name = name.clone();
+            activity
+                .subscribe_to_property(&format!("percentage.{name}"), move |new_value| {
+                    let perc = *cast_dyn_any!(new_value, f64).unwrap();
+                    carousel.update_battery(&prop_name, Some(perc), None);
+                })
+                .unwrap();
+
+            let _ = activity.add_dynamic_property(format!("charging.{name}"), false);
+            let carousel = this.clone();
+            let prop_name = name.clone();
+            activity
+                .subscribe_to_property(&format!("charging.{name}"), move |new_value| {
+                    let charging = *cast_dyn_any!(new_value, bool).unwrap();
+                    carousel.update_battery(&prop_name, None, Some(charging));
+                })
+                .unwrap();
+        }
+
+        if this.imp().auto_advance_interval_ms.get() > 0 {
+            let carousel = this.clone();
+            glib::timeout_add_local(
+                Duration::from_millis(this.imp().auto_advance_interval_ms.get() as u64),
+                move || {
+                    carousel.next();
+                    glib::ControlFlow::Continue
+                },
+            );
+        }
+
+        this
+    }
+
+    /// colors a battery's fill from `color_stops` if any are set, otherwise falls back to a
+    /// binary switch between `low_battery_color` and `normal_color` at `low_battery_threshold`,
+    /// same per-battery color logic as [`Minimal`](super::minimal::Minimal)
+    fn fill_color_for(&self, percentage: f64) -> String {
+        let stops = self.imp().color_stops.borrow();
+        if let Some(color) = color_for_stops(&stops, percentage) {
+            color.to_string()
+        } else if percentage < self.imp().low_battery_threshold.get() {
+            self.imp().low_battery_color.borrow().clone()
+        } else {
+            self.imp().normal_color.borrow().clone()
+        }
+    }
+
+    fn update_battery(&self, name: &str, percentage: Option<f64>, charging: Option<bool>) {
+        let batteries = self.imp().batteries.borrow();
+        let Some(battery) = batteries.get(name) else {
+            log::warn!("Carousel: unknown battery `{name}`");
+            return;
+        };
+        if let Some(percentage) = percentage {
+            battery.set_percentage(percentage);
+        }
+        if let Some(charging) = charging {
+            battery.set_charging(charging);
+        }
+        if !battery.charging() {
+            battery.set_fill_color(self.fill_color_for(battery.percentage()));
+        } else {
+            battery.set_fill_color(self.imp().charging_color.borrow().clone());
+        }
+        drop(batteries);
+        self.queue_draw();
+    }
+
+    pub fn next(&self) {
+        let count = self.imp().order.borrow().len() as u32;
+        if count == 0 {
+            return;
+        }
+        let active = (self.active_index() + 1) % count;
+        self.set_active_index(active);
+        self.queue_draw();
+    }
+
+    pub fn previous(&self) {
+        let count = self.imp().order.borrow().len() as u32;
+        if count == 0 {
+            return;
+        }
+        let active = (self.active_index() + count - 1) % count;
+        self.set_active_index(active);
+        self.queue_draw();
+    }
+}
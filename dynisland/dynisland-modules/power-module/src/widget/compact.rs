@@ -102,6 +102,7 @@ impl Compact {
     /// registered properties:
     /// * `percentage`: `f64`
     /// * `charging`: `bool`
+    /// * `health`: `f64`
     pub fn new(activity: &mut DynamicActivity) -> Self {
         let this: Self = Object::builder().build();
 
@@ -175,7 +176,26 @@ impl Compact {
                     state,
                     upower::device::State::FullyCharged | upower::device::State::Charging
                 );
-                if charging {
+                if matches!(
+                    state,
+                    upower::device::State::Unknown
+                        | upower::device::State::Empty
+                        | upower::device::State::PendingCharge
+                        | upower::device::State::PendingDischarge
+                ) {
+                    // no reliable time estimate in this state, don't invent a "Xh Ym" duration
+                    let percentage = compact.imp().battery.percentage();
+                    if percentage > 0.0 {
+                        compact
+                            .imp()
+                            .label
+                            .set_text(&format!("Battery: {:.0}%", percentage * 100.0));
+                        compact.imp().label.set_width_chars(13);
+                    } else {
+                        compact.imp().label.set_text("Unknown");
+                        compact.imp().label.set_width_chars(7);
+                    }
+                } else if charging {
                     let h = time_to_full / 3600;
                     let m = (time_to_full % 3600) / 60;
                     if matches!(state, upower::device::State::FullyCharged) {
@@ -199,9 +219,19 @@ impl Compact {
                         compact.imp().label.set_width_chars(13);
                     }
                 }
-                //TODO display unknown state
             })
             .unwrap();
+
+        let _ = activity.add_dynamic_property("health", 100.0_f64);
+
+        let compact = this.clone();
+        activity
+            .subscribe_to_property("health", move |new_value| {
+                let health = *cast_dyn_any!(new_value, f64).unwrap();
+                compact.set_tooltip_text(Some(&format!("Health: {health:.0}%")));
+            })
+            .unwrap();
+
         this
     }
 }
@@ -0,0 +1,325 @@
+use std::cell::{Cell, RefCell};
+
+use dyn_fmt::AsStrFormatExt;
+use dynisland_core::{
+    abi::{gdk, glib, gtk, log},
+    cast_dyn_any,
+    dynamic_activity::DynamicActivity,
+};
+use gdk::RGBA;
+use glib::{
+    prelude::ObjectExt,
+    subclass::{
+        object::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
+        types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+        InitializingObject,
+    },
+    types::StaticTypeExt,
+    Object, Properties,
+};
+use gtk::{
+    prelude::WidgetExt,
+    subclass::widget::{
+        CompositeTemplateClass, CompositeTemplateDisposeExt, CompositeTemplateInitializingExt,
+        WidgetClassExt, WidgetImpl,
+    },
+    BinLayout, CompositeTemplate, TemplateChild,
+};
+
+use super::{
+    battery::Battery,
+    minimal::{color_for_stops, format_color_stops, parse_color_stops},
+};
+use crate::upower::{self, device::HistoryEntry};
+
+/// formats a `"time-to"` reading like [`Compact`](super::compact::Compact) does, minus the
+/// fixed-width label padding Compact needs for its terminal-style display; in the states with
+/// no reliable estimate this always reads `Unknown`, where Compact shows the battery percentage
+fn format_time_to(state: upower::device::State, time_to_empty: u64, time_to_full: u64) -> String {
+    use upower::device::State;
+    let charging = matches!(state, State::FullyCharged | State::Charging);
+    if matches!(
+        state,
+        State::Unknown | State::Empty | State::PendingCharge | State::PendingDischarge
+    ) {
+        "Unknown".to_string()
+    } else if charging {
+        if matches!(state, State::FullyCharged) {
+            "Fully Charged".to_string()
+        } else if time_to_full == 0 {
+            "Charging".to_string()
+        } else {
+            format!("{}h{}m to full", time_to_full / 3600, (time_to_full % 3600) / 60)
+        }
+    } else if time_to_empty == 0 {
+        "Discharging".to_string()
+    } else {
+        format!("{}h{}m to empty", time_to_empty / 3600, (time_to_empty % 3600) / 60)
+    }
+}
+
+glib::wrapper! {
+    pub struct Detailed(ObjectSubclass<DetailedPriv>)
+    @extends gtk::Widget;
+}
+
+#[derive(CompositeTemplate, Properties)]
+#[properties(wrapper_type = Detailed)]
+#[template(resource = "/com/github/cr3eperall/dynislandModules/powerModule/detailed.ui")]
+pub struct DetailedPriv {
+    #[template_child]
+    pub battery: TemplateChild<Battery>,
+    #[template_child]
+    pub secondary_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub tertiary_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub quaternary_label: TemplateChild<gtk::Label>,
+    #[property(get, set)]
+    pub low_battery_color: RefCell<String>,
+    #[property(get, set)]
+    pub charging_color: RefCell<String>,
+    #[property(get, set)]
+    pub normal_color: RefCell<String>,
+    /// fraction (`0.0..=1.0`) below which the battery is considered low when `color_stops` is empty
+    #[property(get, set)]
+    pub low_battery_threshold: Cell<f64>,
+    /// `"pos:color,pos:color,..."` gradient stops the fill is interpolated across as it drains,
+    /// e.g. `"0:red,0.5:orange,1:green"`; falls back to a binary `low_battery_color`/`normal_color`
+    /// switch at `low_battery_threshold` when empty
+    #[property(get, set, type=String)]
+    pub color_stops: RefCell<Vec<(f64, RGBA)>>,
+    /// Pango markup for the time-to-empty/time-to-full text, the formatted value is inserted
+    /// in the first `{}` placeholder
+    #[property(get, set)]
+    pub secondary_markup: RefCell<String>,
+    /// Pango markup for the power draw text, the formatted value is inserted in the first
+    /// `{}` placeholder
+    #[property(get, set)]
+    pub tertiary_markup: RefCell<String>,
+    /// Pango markup for the battery health text, the formatted value is inserted in the
+    /// first `{}` placeholder
+    #[property(get, set)]
+    pub quaternary_markup: RefCell<String>,
+}
+
+impl Default for DetailedPriv {
+    fn default() -> Self {
+        Self {
+            battery: TemplateChild::default(),
+            secondary_label: TemplateChild::default(),
+            tertiary_label: TemplateChild::default(),
+            quaternary_label: TemplateChild::default(),
+            low_battery_color: RefCell::new("red".to_string()),
+            charging_color: RefCell::new("green".to_string()),
+            normal_color: RefCell::new("white".to_string()),
+            low_battery_threshold: Cell::new(0.2),
+            color_stops: RefCell::new(Vec::new()),
+            secondary_markup: RefCell::new("{}".to_string()),
+            tertiary_markup: RefCell::new("{}".to_string()),
+            quaternary_markup: RefCell::new("{}".to_string()),
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for DetailedPriv {
+    const NAME: &'static str = "PowerDetailedWidget";
+    type Type = Detailed;
+    type ParentType = gtk::Widget;
+
+    fn class_init(klass: &mut Self::Class) {
+        // if you use custom widgets from core you need to ensure the type
+        Battery::ensure_type();
+        klass.set_layout_manager_type::<BinLayout>();
+        klass.bind_template();
+        // Warning: template callbacks only work if the module is embedded
+        // so don't call `klass.bind_template_instance_callbacks();` or dynisland will crash
+        // manually connect signals in `ObjectImpl::constructed` instead
+    }
+
+    fn instance_init(obj: &InitializingObject<Self>) {
+        obj.init_template();
+    }
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for DetailedPriv {
+    fn constructed(&self) {
+        self.parent_constructed();
+    }
+
+    fn dispose(&self) {
+        while let Some(child) = self.obj().first_child() {
+            child.unparent();
+        }
+        self.dispose_template();
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "low-battery-color" => {
+                self.low_battery_color.replace(value.get().unwrap());
+            }
+            "charging-color" => {
+                self.charging_color.replace(value.get().unwrap());
+            }
+            "normal-color" => {
+                self.normal_color.replace(value.get().unwrap());
+            }
+            "low-battery-threshold" => {
+                self.low_battery_threshold.set(value.get().unwrap());
+            }
+            "color-stops" => {
+                let name: String = value.get().unwrap();
+                if let Some(stops) = parse_color_stops(&name) {
+                    self.color_stops.replace(stops);
+                } else {
+                    log::warn!("invalid color stops: {name}");
+                }
+            }
+            "secondary-markup" => {
+                self.secondary_markup.replace(value.get().unwrap());
+            }
+            "tertiary-markup" => {
+                self.tertiary_markup.replace(value.get().unwrap());
+            }
+            "quaternary-markup" => {
+                self.quaternary_markup.replace(value.get().unwrap());
+            }
+            _ => {
+                log::warn!("Detailed: invalid property received: {}", pspec.name());
+            }
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "low-battery-color" => self.low_battery_color.borrow().to_value(),
+            "charging-color" => self.charging_color.borrow().to_value(),
+            "normal-color" => self.normal_color.borrow().to_value(),
+            "low-battery-threshold" => self.low_battery_threshold.get().to_value(),
+            "color-stops" => format_color_stops(&self.color_stops.borrow()).to_value(),
+            "secondary-markup" => self.secondary_markup.borrow().to_value(),
+            "tertiary-markup" => self.tertiary_markup.borrow().to_value(),
+            "quaternary-markup" => self.quaternary_markup.borrow().to_value(),
+            _ => {
+                log::warn!("Detailed: invalid property received: {}", pspec.name());
+                glib::Value::from_type(glib::Type::UNIT)
+            }
+        }
+    }
+}
+
+impl WidgetImpl for DetailedPriv {}
+
+impl Detailed {
+    /// registered properties:
+    /// * `percentage`: `f64`
+    /// * `charging`: `bool`
+    /// * `time-to`: `(State, u64, u64)`, same tuple [`Compact`](super::compact::Compact) formats
+    /// * `points`: `Vec<HistoryEntry>`, same history [`Expanded`](super::expanded::Expanded) reads
+    ///   its latest power draw from
+    /// * `health`: `f64`, same property [`Compact`](super::compact::Compact) shares
+    pub fn new(activity: &mut DynamicActivity) -> Self {
+        let this: Self = Object::builder().build();
+
+        // register the property if it doesn't exist
+        // this way we can update multiple widgets with the same property
+        let _ = activity.add_dynamic_property("percentage", 0.0_f64);
+
+        let detailed = this.clone();
+        activity
+            .subscribe_to_property("percentage", move |new_value| {
+                let perc = *cast_dyn_any!(new_value, f64).unwrap();
+                detailed.imp().battery.set_percentage(perc);
+                if !detailed.imp().battery.charging() {
+                    let color = detailed.fill_color_for(detailed.imp().battery.percentage());
+                    detailed.imp().battery.set_fill_color(color);
+                } else {
+                    detailed
+                        .imp()
+                        .battery
+                        .set_fill_color(detailed.imp().charging_color.borrow().clone());
+                }
+            })
+            .unwrap();
+
+        let _ = activity.add_dynamic_property("charging", false);
+
+        let detailed = this.clone();
+        activity
+            .subscribe_to_property("charging", move |new_value| {
+                let charging = *cast_dyn_any!(new_value, bool).unwrap();
+                detailed.imp().battery.set_charging(charging);
+            })
+            .unwrap();
+
+        // reuse the same "time-to" tuple the producer already publishes for `Compact`, instead
+        // of a Detailed-only property nothing else would ever set
+        let _ = activity
+            .add_dynamic_property("time-to", (upower::device::State::Unknown, 0_u64, 0_u64));
+
+        let detailed = this.clone();
+        activity
+            .subscribe_to_property("time-to", move |new_value| {
+                let (state, time_to_empty, time_to_full) =
+                    *cast_dyn_any!(new_value, (upower::device::State, u64, u64)).unwrap();
+                let text = format_time_to(state, time_to_empty, time_to_full);
+                let markup = detailed.imp().secondary_markup.borrow().format(&[text]);
+                detailed.imp().secondary_label.set_markup(&markup);
+            })
+            .unwrap();
+
+        // reuse the same "points" history the producer already publishes for `Expanded`,
+        // instead of a Detailed-only property nothing else would ever set
+        let _ = activity.add_dynamic_property("points", Vec::<HistoryEntry>::new());
+
+        let detailed = this.clone();
+        activity
+            .subscribe_to_property("points", move |new_value| {
+                let points = cast_dyn_any!(new_value, Vec::<HistoryEntry>).unwrap();
+                let Some(latest) = points.last() else {
+                    return;
+                };
+                let text = format!("{:.1} W", latest.signed_power_draw());
+                let markup = detailed.imp().tertiary_markup.borrow().format(&[text]);
+                detailed.imp().tertiary_label.set_markup(&markup);
+            })
+            .unwrap();
+
+        // `add_dynamic_property` only registers a property if it's absent, so this must match
+        // `Compact`'s `f64` registration exactly or whichever widget is built second silently
+        // gets the other's type and panics the first time a value is set
+        let _ = activity.add_dynamic_property("health", 100.0_f64);
+
+        let detailed = this.clone();
+        activity
+            .subscribe_to_property("health", move |new_value| {
+                let health = *cast_dyn_any!(new_value, f64).unwrap();
+                let markup = detailed
+                    .imp()
+                    .quaternary_markup
+                    .borrow()
+                    .format(&[format!("{health:.0}%")]);
+                detailed.imp().quaternary_label.set_markup(&markup);
+            })
+            .unwrap();
+
+        this
+    }
+
+    /// colors the battery fill from `color_stops` if any are set, otherwise falls back to a
+    /// binary switch between `low_battery_color` and `normal_color` at `low_battery_threshold`,
+    /// same per-battery color logic as [`Minimal`](super::minimal::Minimal)
+    fn fill_color_for(&self, percentage: f64) -> String {
+        let stops = self.imp().color_stops.borrow();
+        if let Some(color) = color_for_stops(&stops, percentage) {
+            color.to_string()
+        } else if percentage < self.imp().low_battery_threshold.get() {
+            self.imp().low_battery_color.borrow().clone()
+        } else {
+            self.imp().normal_color.borrow().clone()
+        }
+    }
+}
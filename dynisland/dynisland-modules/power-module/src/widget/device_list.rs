@@ -0,0 +1,271 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+};
+
+use dynisland_core::{
+    abi::{glib, gtk},
+    cast_dyn_any,
+    dynamic_activity::DynamicActivity,
+};
+use glib::{
+    prelude::*,
+    subclass::{
+        object::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
+        types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+    },
+    Object, Properties,
+};
+use gtk::{
+    gio,
+    prelude::*,
+    subclass::widget::{WidgetClassExt, WidgetImpl},
+    BinLayout, ListItem, ListView, NoSelection, SignalListItemFactory, TreeExpander,
+    TreeListModel, TreeListRow,
+};
+
+use super::{battery::Battery, graph::Graph};
+use crate::upower::device::{DeviceId, HistoryEntry, State};
+
+glib::wrapper! {
+    /// one device's id and history, wrapped as a plain GObject so it can live in the
+    /// [`gio::ListStore`] backing [`DeviceList`]'s [`TreeListModel`]
+    struct DeviceRow(ObjectSubclass<DeviceRowPriv>);
+}
+
+#[derive(Default)]
+struct DeviceRowPriv {
+    device_id: RefCell<DeviceId>,
+    points: RefCell<Vec<HistoryEntry>>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for DeviceRowPriv {
+    const NAME: &'static str = "PowerDeviceListRow";
+    type Type = DeviceRow;
+    type ParentType = glib::Object;
+}
+
+impl ObjectImpl for DeviceRowPriv {}
+
+impl DeviceRow {
+    fn new(device_id: DeviceId, points: Vec<HistoryEntry>) -> Self {
+        let this: Self = Object::builder().build();
+        this.imp().device_id.replace(device_id);
+        this.imp().points.replace(points);
+        this
+    }
+
+    fn device_id(&self) -> DeviceId {
+        self.imp().device_id.borrow().clone()
+    }
+
+    fn points(&self) -> Vec<HistoryEntry> {
+        self.imp().points.borrow().clone()
+    }
+
+    fn set_points(&self, points: Vec<HistoryEntry>) {
+        self.imp().points.replace(points);
+    }
+}
+
+glib::wrapper! {
+    pub struct DeviceList(ObjectSubclass<DeviceListPriv>)
+    @extends gtk::Widget;
+}
+
+#[derive(Properties)]
+#[properties(wrapper_type = DeviceList)]
+pub struct DeviceListPriv {
+    #[property(get, set)]
+    pub low_battery_color: RefCell<String>,
+    #[property(get, set)]
+    pub charging_color: RefCell<String>,
+    #[property(get, set)]
+    pub normal_color: RefCell<String>,
+    /// fraction (`0.0..=1.0`) below which a device's graph switches from `normal_color` to
+    /// `low_battery_color`, forwarded to each detail row's [`Graph`] when it is bound; the
+    /// summary row's battery icon is not recolored
+    #[property(get, set)]
+    pub low_battery_threshold: Cell<f64>,
+
+    list_view: RefCell<Option<ListView>>,
+    model: RefCell<Option<gio::ListStore>>,
+}
+
+impl Default for DeviceListPriv {
+    fn default() -> Self {
+        Self {
+            low_battery_color: RefCell::new("red".to_string()),
+            charging_color: RefCell::new("green".to_string()),
+            normal_color: RefCell::new("white".to_string()),
+            low_battery_threshold: Cell::new(0.2),
+            list_view: RefCell::new(None),
+            model: RefCell::new(None),
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for DeviceListPriv {
+    const NAME: &'static str = "PowerDeviceListWidget";
+    type Type = DeviceList;
+    type ParentType = gtk::Widget;
+
+    fn class_init(klass: &mut Self::Class) {
+        // if you use custom widgets from core you need to ensure the type
+        Battery::ensure_type();
+        Graph::ensure_type();
+        klass.set_layout_manager_type::<BinLayout>();
+        klass.set_css_name("power-device-list-widget");
+    }
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for DeviceListPriv {
+    fn constructed(&self) {
+        self.parent_constructed();
+
+        let model = gio::ListStore::new(DeviceRow::static_type());
+
+        // every top-level row gets exactly one child: itself, again. This lets the factory below
+        // tell a device's summary row (depth 0) apart from its expanded detail row (depth 1)
+        // while reusing the same `DeviceRow` data. The detail row gets a child model too, but
+        // the factory unbinds its expander, so it is never expanded
+        let tree_model = TreeListModel::new(model.clone(), false, false, |item: &glib::Object| {
+            let row = item.downcast_ref::<DeviceRow>()?;
+            let detail = gio::ListStore::new(DeviceRow::static_type());
+            detail.append(row);
+            Some(detail.upcast())
+        });
+
+        let list = self.obj().downgrade();
+
+        let factory = SignalListItemFactory::new();
+        factory.connect_setup(|_factory, list_item| {
+            let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+            list_item.set_child(Some(&TreeExpander::new()));
+        });
+        factory.connect_bind(move |_factory, list_item| {
+            let list_item = list_item.downcast_ref::<ListItem>().unwrap();
+            let Some(tree_row) = list_item.item().and_downcast::<TreeListRow>() else {
+                return;
+            };
+            let Some(device_row) = tree_row.item().and_downcast::<DeviceRow>() else {
+                return;
+            };
+            let Some(expander) = list_item.child().and_downcast::<TreeExpander>() else {
+                return;
+            };
+
+            if tree_row.depth() == 0 {
+                // summary row: a compact battery icon the user can click to expand
+                expander.set_list_row(Some(&tree_row));
+                // a recycled expander may still be hidden from a previous detail-row bind
+                expander.set_hide_expander(false);
+                let battery = Battery::new();
+                let last = device_row.points().last().copied();
+                battery.set_percentage(last.map_or(0.0, |entry| entry.value) / 100.0);
+                battery.set_charging(last.is_some_and(|entry| {
+                    matches!(entry.state, State::Charging | State::FullyCharged)
+                }));
+                expander.set_child(Some(&battery));
+            } else {
+                // expanded detail row: unbind and hide its expander so it can't be expanded
+                // further, and show the full history graph instead
+                expander.set_list_row(None::<&TreeListRow>);
+                expander.set_hide_expander(true);
+                let Some(list) = list.upgrade() else {
+                    return;
+                };
+                let imp = list.imp();
+                let graph = Graph::new();
+                graph.set_low_battery_color(imp.low_battery_color.borrow().clone());
+                graph.set_charging_color(imp.charging_color.borrow().clone());
+                graph.set_normal_color(imp.normal_color.borrow().clone());
+                graph.set_low_battery_threshold(imp.low_battery_threshold.get());
+                graph.set_points(&device_row.points());
+                expander.set_child(Some(&graph));
+            }
+        });
+
+        let selection = NoSelection::new(Some(tree_model));
+        let list_view = ListView::new(Some(selection), Some(factory));
+        list_view.set_parent(&*self.obj());
+
+        self.list_view.replace(Some(list_view));
+        self.model.replace(Some(model));
+    }
+
+    fn dispose(&self) {
+        if let Some(list_view) = self.list_view.take() {
+            list_view.unparent();
+        }
+    }
+}
+
+impl WidgetImpl for DeviceListPriv {}
+
+impl DeviceList {
+    /// registered properties:
+    /// * `devices`: `Vec<(DeviceId, Vec<HistoryEntry>)>`
+    pub fn new(activity: &mut DynamicActivity) -> Self {
+        let this: Self = Object::builder().build();
+
+        let _ = activity
+            .add_dynamic_property("devices", Vec::<(DeviceId, Vec<HistoryEntry>)>::new());
+
+        let list = this.clone();
+        activity
+            .subscribe_to_property("devices", move |new_value| {
+                let devices =
+                    cast_dyn_any!(new_value, Vec::<(DeviceId, Vec<HistoryEntry>)>).unwrap();
+                list.set_devices(devices);
+            })
+            .unwrap();
+
+        this
+    }
+
+    /// reconciles the backing model against `devices` in place: updates the points of rows that
+    /// are still present, appends rows for newly seen devices, and drops rows for devices that
+    /// disappeared, so a row the user has expanded doesn't collapse just because its history
+    /// got refreshed
+    fn set_devices(&self, devices: &Vec<(DeviceId, Vec<HistoryEntry>)>) {
+        let imp = self.imp();
+        let Some(model) = imp.model.borrow().clone() else {
+            return;
+        };
+
+        let mut seen = HashSet::new();
+        for (device_id, points) in devices {
+            seen.insert(device_id.clone());
+            let existing = (0..model.n_items()).find_map(|i| {
+                let row = model.item(i)?.downcast::<DeviceRow>().ok()?;
+                (row.device_id() == *device_id).then_some((i, row))
+            });
+            match existing {
+                Some((i, row)) => {
+                    row.set_points(points.clone());
+                    // `DeviceRow` isn't itself bindable/notify-able, so the bound `ListView`
+                    // won't know to rebind this row's list items unless told the item at `i`
+                    // changed, even though the underlying `gio::ListStore` object is the same.
+                    model.items_changed(i, 1, 1);
+                }
+                None => model.append(&DeviceRow::new(device_id.clone(), points.clone())),
+            }
+        }
+
+        let mut stale = Vec::new();
+        for i in 0..model.n_items() {
+            if let Some(row) = model.item(i).and_downcast::<DeviceRow>() {
+                if !seen.contains(&row.device_id()) {
+                    stale.push(i);
+                }
+            }
+        }
+        for i in stale.into_iter().rev() {
+            model.remove(i);
+        }
+    }
+}
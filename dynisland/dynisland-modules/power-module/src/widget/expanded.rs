@@ -1,9 +1,10 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use dynisland_core::{
     abi::{
         glib,
-        gtk::{self, EventControllerScroll, EventControllerScrollFlags},
+        gtk::{self, EventControllerScroll, EventControllerScrollFlags, PropagationLimit},
+        log,
     },
     cast_dyn_any,
     dynamic_activity::DynamicActivity,
@@ -19,7 +20,7 @@ use glib::{
     Object, Properties,
 };
 use gtk::{
-    prelude::WidgetExt,
+    prelude::{EventControllerExt, WidgetExt},
     subclass::widget::{
         CompositeTemplateClass, CompositeTemplateDisposeExt, CompositeTemplateInitializingExt,
         WidgetClassExt, WidgetImpl,
@@ -47,7 +48,32 @@ pub struct ExpandedPriv {
     pub charging_color: RefCell<String>,
     #[property(get, set)]
     pub normal_color: RefCell<String>,
-    //TODO add low battery treshold
+    /// fraction (`0.0..=1.0`) below which the graph switches a segment from `normal_color` to
+    /// `low_battery_color`
+    #[property(get, set)]
+    pub low_battery_threshold: Cell<f64>,
+    /// whether a flick on a touchpad keeps panning/zooming the graph and eases to a stop
+    /// instead of stopping dead when the fingers lift; disable for mouse-wheel-only setups
+    #[property(get, set)]
+    pub kinetic_scrolling: Cell<bool>,
+    /// battery percentage from the most recent `points` entry, so a parent widget can show it
+    /// without re-parsing the history vector itself
+    #[property(get = Self::current_percentage, type = f64)]
+    pub current_percentage: Cell<f64>,
+    /// instantaneous power draw in watts from the most recent `points` entry, negative while
+    /// discharging and positive while charging, `0.0` when unknown
+    #[property(get = Self::energy_rate, type = f64)]
+    pub energy_rate: Cell<f64>,
+
+    scroll_controller: RefCell<Option<EventControllerScroll>>,
+
+    /// current (x, y) flick velocity driving the deceleration animation below, decayed toward
+    /// zero over wall-clock time
+    decel_velocity: Cell<(f64, f64)>,
+    /// `clock.frame_time()` (microseconds) from the previous decelerate tick, used to compute
+    /// the elapsed time since then; `None` right after the animation (re)starts
+    decel_last_frame_time: Cell<Option<i64>>,
+    decel_tick_callback_id: RefCell<Option<gtk::TickCallbackId>>,
 }
 
 impl Default for ExpandedPriv {
@@ -57,6 +83,14 @@ impl Default for ExpandedPriv {
             low_battery_color: RefCell::new("red".to_string()),
             charging_color: RefCell::new("green".to_string()),
             normal_color: RefCell::new("white".to_string()),
+            low_battery_threshold: Cell::new(0.2),
+            kinetic_scrolling: Cell::new(true),
+            current_percentage: Cell::new(0.0),
+            energy_rate: Cell::new(0.0),
+            scroll_controller: RefCell::new(None),
+            decel_velocity: Cell::new((0.0, 0.0)),
+            decel_last_frame_time: Cell::new(None),
+            decel_tick_callback_id: RefCell::new(None),
         }
     }
 }
@@ -94,26 +128,124 @@ impl ObjectImpl for ExpandedPriv {
         }
         self.dispose_template();
     }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "low-battery-color" => {
+                let name: String = value.get().unwrap();
+                self.graph.set_low_battery_color(name.clone());
+                self.low_battery_color.replace(name);
+            }
+            "charging-color" => {
+                let name: String = value.get().unwrap();
+                self.graph.set_charging_color(name.clone());
+                self.charging_color.replace(name);
+            }
+            "normal-color" => {
+                let name: String = value.get().unwrap();
+                self.graph.set_normal_color(name.clone());
+                self.normal_color.replace(name);
+            }
+            "low-battery-threshold" => {
+                let threshold: f64 = value.get().unwrap();
+                self.graph.set_low_battery_threshold(threshold);
+                self.low_battery_threshold.set(threshold);
+            }
+            "kinetic-scrolling" => {
+                let kinetic: bool = value.get().unwrap();
+                self.kinetic_scrolling.set(kinetic);
+                self.obj().rebuild_scroll_controller();
+            }
+            _ => {
+                log::warn!("Expanded: invalid property received: {}", pspec.name());
+            }
+        }
+    }
+
+    fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "low-battery-color" => self.low_battery_color.borrow().to_value(),
+            "charging-color" => self.charging_color.borrow().to_value(),
+            "normal-color" => self.normal_color.borrow().to_value(),
+            "low-battery-threshold" => self.low_battery_threshold.get().to_value(),
+            "kinetic-scrolling" => self.kinetic_scrolling.get().to_value(),
+            "current-percentage" => self.current_percentage().to_value(),
+            "energy-rate" => self.energy_rate().to_value(),
+            _ => self.derived_property(id, pspec),
+        }
+    }
 }
 
 impl WidgetImpl for ExpandedPriv {}
 
+impl ExpandedPriv {
+    fn current_percentage(&self) -> f64 {
+        self.current_percentage.get()
+    }
+
+    fn energy_rate(&self) -> f64 {
+        self.energy_rate.get()
+    }
+
+    /// updates `current-percentage`/`energy-rate` from the most recent entry in `points`,
+    /// notifying both so a bound parent widget picks up the change
+    fn update_latest(&self, latest: &HistoryEntry) {
+        self.current_percentage.set(latest.value);
+        self.energy_rate.set(latest.signed_power_draw());
+        let obj = self.obj();
+        obj.notify_current_percentage();
+        obj.notify_energy_rate();
+    }
+
+    /// starts the frame-clock tick callback that decays `decel_velocity` toward zero, panning
+    /// and resizing the graph's view each frame, mirroring [`Battery`]'s `animate_charging`
+    /// tick-callback pattern. A no-op if already running.
+    fn start_decelerate_animation(&self) {
+        if self.decel_tick_callback_id.borrow().is_some() {
+            return;
+        }
+        self.decel_last_frame_time.set(None);
+        let id = self.obj().add_tick_callback(|widget, clock| {
+            let imp = widget.imp();
+            let (x_vel, y_vel) = imp.decel_velocity.get();
+            if x_vel.abs() < 0.01 && y_vel.abs() < 0.01 {
+                imp.decel_tick_callback_id.take();
+                imp.decel_last_frame_time.set(None);
+                return glib::ControlFlow::Break;
+            }
+            let gr = &imp.graph;
+            gr.set_max_duration_secs((gr.max_duration_secs() as f64 + (y_vel * 8.0)) as u32);
+            let new_offset = gr.view_offset_secs() as i64 - (x_vel * 8.0) as i64;
+            gr.set_view_offset_secs(new_offset.max(0) as u32);
+            gr.queue_draw();
+
+            // `frame_time()` is in microseconds; derive the actual elapsed time since the
+            // previous tick instead of assuming one tick == one fixed-length frame, so the
+            // decay plays out over the same wall-clock time on a 60Hz and a 144Hz display
+            let now = clock.frame_time();
+            let elapsed_secs = match imp.decel_last_frame_time.replace(Some(now)) {
+                Some(prev) => (now - prev).max(0) as f64 / 1_000_000.0,
+                None => 1.0 / 60.0,
+            };
+            // equivalent to the old `* 0.92` per tick at a 60Hz refresh rate, but now applied
+            // as a continuous per-second rate so the velocity halves on the same schedule
+            // regardless of how often the frame clock actually ticks
+            const DECAY_PER_SECOND: f64 = 5.0;
+            let factor = (-DECAY_PER_SECOND * elapsed_secs).exp();
+            imp.decel_velocity.set((x_vel * factor, y_vel * factor));
+            glib::ControlFlow::Continue
+        });
+        self.decel_tick_callback_id.replace(Some(id));
+    }
+}
+
 impl Expanded {
     /// registered properties:
     /// * `points`: `Vec<(u64, f64)>`
+    /// * `charging`: `bool`
     pub fn new(activity: &mut DynamicActivity) -> Self {
         let this: Self = Object::builder().build();
-        let contr = EventControllerScroll::new(
-            EventControllerScrollFlags::VERTICAL.union(EventControllerScrollFlags::HORIZONTAL),
-        );
-        let gr = this.imp().graph.clone();
-        contr.connect_scroll(move |_ev, _x, y| {
-            // log::debug!("scrolling, {:?}, x:{x}, y:{y}", ev.current_event_state());
-            gr.set_max_duration_secs((gr.max_duration_secs() as f64 + (y * 80.0)) as u32);
-            gr.queue_draw();
-            glib::Propagation::Proceed
-        });
-        this.add_controller(contr);
+        this.rebuild_scroll_controller();
 
         // register the property if it doesn't exist
         // this way we can update multiple widgets with the same property
@@ -124,24 +256,62 @@ impl Expanded {
             .subscribe_to_property("points", move |new_value| {
                 let points = cast_dyn_any!(new_value, Vec::<HistoryEntry>).unwrap();
                 minimal.imp().graph.set_points(points);
+                if let Some(latest) = points.last() {
+                    minimal.imp().update_latest(latest);
+                }
             })
             .unwrap();
 
-        // let _ = activity.add_dynamic_property("charging", false);
-
-        // let minimal = this.clone();
-        // activity
-        //     .subscribe_to_property("charging", move |new_value| {
-        //         let charging = *cast_dyn_any!(new_value, bool).unwrap();
-        //         minimal.imp().battery.set_charging(charging);
-        //         if charging {
-        //             minimal
-        //                 .imp()
-        //                 .battery
-        //                 .set_fill_color(minimal.imp().charging_color.borrow().clone());
-        //         }
-        //     })
-        //     .unwrap();
+        // register the property if it doesn't exist
+        // this way we can update multiple widgets with the same property
+        // the graph itself colors each sample from its recorded state, so there's nothing to
+        // subscribe to here, but registering it keeps it available to other widgets sharing
+        // this activity
+        let _ = activity.add_dynamic_property("charging", false);
+
         this
     }
+
+    /// (re)creates the graph's scroll controller, picking up the current `kinetic-scrolling`
+    /// value; called once from `new` and again whenever that property is toggled, since
+    /// `EventControllerScrollFlags` can't be changed on a live controller
+    fn rebuild_scroll_controller(&self) {
+        if let Some(old) = self.imp().scroll_controller.take() {
+            self.remove_controller(&old);
+        }
+
+        let mut flags =
+            EventControllerScrollFlags::VERTICAL.union(EventControllerScrollFlags::HORIZONTAL);
+        if self.kinetic_scrolling() {
+            flags = flags.union(EventControllerScrollFlags::KINETIC);
+        }
+        let contr = EventControllerScroll::new(flags);
+        // scroll events over the expanded graph shouldn't also scroll the surrounding
+        // dynisland layout
+        contr.set_propagation_limit(PropagationLimit::SameNative);
+
+        let gr = self.imp().graph.clone();
+        contr.connect_scroll(move |_ev, x, y| {
+            // log::debug!("scrolling, {:?}, x:{x}, y:{y}", ev.current_event_state());
+            gr.set_max_duration_secs((gr.max_duration_secs() as f64 + (y * 80.0)) as u32);
+            // scrolling right pans toward "now" (decreasing the offset), scrolling left pans
+            // further into the past; `Graph::set_view_offset_secs` clamps against the oldest
+            // sample, and `max(0)` below keeps the view from panning into the future
+            let new_offset = gr.view_offset_secs() as i64 - (x * 80.0) as i64;
+            gr.set_view_offset_secs(new_offset.max(0) as u32);
+            gr.queue_draw();
+            glib::Propagation::Proceed
+        });
+
+        let expanded = self.clone();
+        contr.connect_decelerate(move |_ev, x_vel, y_vel| {
+            // a flick keeps panning/zooming at the reported velocity and eases toward zero
+            // over subsequent frames, same direction convention as `connect_scroll` above
+            expanded.imp().decel_velocity.set((x_vel, y_vel));
+            expanded.imp().start_decelerate_animation();
+        });
+
+        self.add_controller(contr.clone());
+        self.imp().scroll_controller.replace(Some(contr));
+    }
 }
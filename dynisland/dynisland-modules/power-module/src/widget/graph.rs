@@ -1,4 +1,8 @@
-use std::{cell::RefCell, str::FromStr};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    str::FromStr,
+};
 
 use chrono::{DateTime, Days, Local, Timelike};
 use dynisland_core::abi::{gdk, glib, gtk, log};
@@ -14,16 +18,52 @@ use gtk::{
     graphene::Rect,
     prelude::*,
     subclass::widget::{WidgetClassExt, WidgetImpl},
-    BinLayout,
+    BinLayout, EventControllerMotion,
 };
 use pangocairo::glib::subclass::types::ObjectSubclassIsExt;
 
-use crate::upower::device::HistoryEntry;
+use crate::upower::device::{HistoryEntry, State};
 
 glib::wrapper! {
     pub struct Graph(ObjectSubclass<GraphPriv>)
     @extends gtk::Widget;
 }
+
+/// ring buffer of [`HistoryEntry`] rows, oldest first, that evicts entries older than a given
+/// window as new ones are pushed instead of letting the history grow unbounded. Pushes must
+/// arrive in non-decreasing timestamp order, same requirement as the old `Vec`-based storage.
+#[derive(Default)]
+struct HistoryRing {
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl HistoryRing {
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// appends `entry` and evicts anything older than `window_secs` relative to `entry`'s
+    /// own timestamp, so bulk-loading old history doesn't get evicted against the live clock
+    fn push(&mut self, entry: HistoryEntry, window_secs: u32) {
+        self.entries.push_back(entry);
+        let min_limit = entry.timestamp.saturating_sub(window_secs);
+        while matches!(self.entries.front(), Some(front) if front.timestamp < min_limit) {
+            self.entries.pop_front();
+        }
+    }
+
+    fn min_timestamp(&self) -> Option<u32> {
+        self.entries.front().map(|entry| entry.timestamp)
+    }
+
+    fn last(&self) -> Option<HistoryEntry> {
+        self.entries.back().copied()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+}
 #[derive(Properties)]
 #[properties(wrapper_type = Graph)]
 pub struct GraphPriv {
@@ -31,8 +71,52 @@ pub struct GraphPriv {
     max_duration_secs: RefCell<u32>,
     #[property(get, set)]
     draw_bars: RefCell<bool>,
+    /// fixed ceiling for the secondary (power draw) axis in watts, `0.0` means auto-compute
+    /// from the points currently in view
+    #[property(get, set)]
+    max_watts: Cell<f64>,
+    /// if true, the auto-computed watts ceiling only ever grows, so the overlay never
+    /// rescales downward between snapshots
+    #[property(get, set)]
+    keep_max_watts: Cell<bool>,
+    /// plot the long-term battery health (capacity degradation) trend instead of the
+    /// percentage/watts history
+    #[property(get, set)]
+    draw_health: Cell<bool>,
+    /// strftime format for the time-axis label, e.g. `%H:%M` or `%I:%M %p` for a 12-hour clock
+    #[property(get, set)]
+    time_format: RefCell<String>,
+    /// strftime format for the date-axis label, e.g. `%d/%m` or a locale-specific `%x`
+    #[property(get, set)]
+    date_format: RefCell<String>,
+    /// fraction (`0.0..=1.0`) below which a segment is painted with `low_battery_color`
+    /// instead of `normal_color`
+    #[property(get, set)]
+    low_battery_threshold: Cell<f64>,
+    /// color for segments below `low_battery_threshold`
+    #[property(get, set, type=String)]
+    low_battery_color: RefCell<RGBA>,
+    /// color for segments where the battery is charging or fully charged
+    #[property(get, set, type=String)]
+    charging_color: RefCell<RGBA>,
+    /// color for segments that are neither charging nor below `low_battery_threshold`
+    #[property(get, set, type=String)]
+    normal_color: RefCell<RGBA>,
+    /// seconds to pan the visible window back from "now"; the graph shows
+    /// `[now - view_offset_secs - max_duration_secs, now - view_offset_secs]`, clamped so it
+    /// can't pan past the oldest sample
+    #[property(get, set)]
+    view_offset_secs: Cell<u32>,
+    /// draw a label at the most recent point when the pointer isn't hovering the graph, so the
+    /// current value stays visible without needing to mouse over it
+    #[property(get, set)]
+    annotate_latest: Cell<bool>,
 
-    points: RefCell<Vec<HistoryEntry>>,
+    points: RefCell<HistoryRing>,
+    auto_max_watts: Cell<f64>,
+    /// x position of the pointer within the graph area, in widget-local coordinates, while it's
+    /// hovering over the widget; `None` when the pointer isn't over it
+    hover_x: RefCell<Option<f64>>,
 }
 
 #[glib::object_subclass]
@@ -52,7 +136,20 @@ impl Default for GraphPriv {
         Self {
             max_duration_secs: RefCell::new(36000),
             draw_bars: RefCell::new(false),
-            points: RefCell::new(Vec::new()),
+            max_watts: Cell::new(0.0),
+            keep_max_watts: Cell::new(false),
+            draw_health: Cell::new(false),
+            time_format: RefCell::new("%H:%M".to_string()),
+            date_format: RefCell::new("%d/%m".to_string()),
+            low_battery_threshold: Cell::new(0.2),
+            low_battery_color: RefCell::new(RGBA::parse("red").unwrap()),
+            charging_color: RefCell::new(RGBA::parse("green").unwrap()),
+            normal_color: RefCell::new(RGBA::WHITE),
+            view_offset_secs: Cell::new(0),
+            annotate_latest: Cell::new(true),
+            points: RefCell::new(HistoryRing::default()),
+            auto_max_watts: Cell::new(0.0),
+            hover_x: RefCell::new(None),
         }
     }
 }
@@ -66,6 +163,19 @@ impl ObjectImpl for GraphPriv {
         //     battery.queue_draw();
         //     glib::ControlFlow::Continue
         // });
+
+        let motion = EventControllerMotion::new();
+        let graph = self.obj().clone();
+        motion.connect_motion(move |_, x, _y| {
+            graph.imp().hover_x.replace(Some(x));
+            graph.queue_draw();
+        });
+        let graph = self.obj().clone();
+        motion.connect_leave(move |_| {
+            graph.imp().hover_x.replace(None);
+            graph.queue_draw();
+        });
+        self.obj().add_controller(motion);
     }
 
     fn dispose(&self) {
@@ -78,11 +188,10 @@ impl ObjectImpl for GraphPriv {
         match pspec.name() {
             "max-duration-secs" => {
                 let mut max_duration_secs: u32 = value.get().unwrap();
-                let points = self.points.borrow();
-                let min_point = points.iter().min_by_key(|entry| entry.timestamp);
-                if let Some(min_point) = min_point {
+                let min_timestamp = self.points.borrow().min_timestamp();
+                if let Some(min_timestamp) = min_timestamp {
                     let now = Local::now().timestamp() as u32;
-                    let max_dur = now - min_point.timestamp;
+                    let max_dur = now - min_timestamp;
                     if max_dur < max_duration_secs
                         && max_duration_secs < self.max_duration_secs.borrow().clone()
                     {
@@ -93,11 +202,76 @@ impl ObjectImpl for GraphPriv {
                     .replace(max_duration_secs.max(60 * 4));
                 self.obj().queue_draw();
             }
+            "view-offset-secs" => {
+                let mut view_offset_secs: u32 = value.get().unwrap();
+                if let Some(min_timestamp) = self.points.borrow().min_timestamp() {
+                    let now = Local::now().timestamp() as u32;
+                    // don't let the right edge of the view pan past the oldest sample
+                    view_offset_secs = view_offset_secs.min(now.saturating_sub(min_timestamp));
+                }
+                self.view_offset_secs.set(view_offset_secs);
+                self.obj().queue_draw();
+            }
+            "annotate-latest" => {
+                self.annotate_latest.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
             "draw-bars" => {
                 let draw_bars: bool = value.get().unwrap();
                 self.draw_bars.replace(draw_bars);
                 self.obj().queue_draw();
             }
+            "max-watts" => {
+                self.max_watts.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "keep-max-watts" => {
+                self.keep_max_watts.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "draw-health" => {
+                self.draw_health.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "time-format" => {
+                self.time_format.replace(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "date-format" => {
+                self.date_format.replace(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "low-battery-threshold" => {
+                self.low_battery_threshold.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "low-battery-color" => {
+                let name: String = value.get().unwrap();
+                if let Ok(color) = RGBA::parse(&name) {
+                    self.low_battery_color.replace(color);
+                    self.obj().queue_draw();
+                } else {
+                    log::warn!("invalid low battery color: {name}");
+                }
+            }
+            "charging-color" => {
+                let name: String = value.get().unwrap();
+                if let Ok(color) = RGBA::parse(&name) {
+                    self.charging_color.replace(color);
+                    self.obj().queue_draw();
+                } else {
+                    log::warn!("invalid charging color: {name}");
+                }
+            }
+            "normal-color" => {
+                let name: String = value.get().unwrap();
+                if let Ok(color) = RGBA::parse(&name) {
+                    self.normal_color.replace(color);
+                    self.obj().queue_draw();
+                } else {
+                    log::warn!("invalid normal color: {name}");
+                }
+            }
             // "fill-color" => {
             //     let name: String = value.get().unwrap();
             //     if let Ok(color) = RGBA::parse(&name) {
@@ -145,7 +319,18 @@ impl ObjectImpl for GraphPriv {
     fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
         match pspec.name() {
             "max-duration-secs" => self.max_duration_secs.borrow().to_value(),
+            "view-offset-secs" => self.view_offset_secs.get().to_value(),
+            "annotate-latest" => self.annotate_latest.get().to_value(),
             "draw-bars" => self.draw_bars.borrow().to_value(),
+            "max-watts" => self.max_watts.get().to_value(),
+            "keep-max-watts" => self.keep_max_watts.get().to_value(),
+            "draw-health" => self.draw_health.get().to_value(),
+            "time-format" => self.time_format.borrow().to_value(),
+            "date-format" => self.date_format.borrow().to_value(),
+            "low-battery-threshold" => self.low_battery_threshold.get().to_value(),
+            "low-battery-color" => self.low_battery_color.borrow().to_string().to_value(),
+            "charging-color" => self.charging_color.borrow().to_string().to_value(),
+            "normal-color" => self.normal_color.borrow().to_string().to_value(),
             // "fill-color" => self.fill_color.borrow().to_string().to_value(),
             // "background-color" => self.background_color.borrow().to_string().to_value(),
             // "percentage" => self.percentage.borrow().to_value(),
@@ -188,13 +373,11 @@ impl WidgetImpl for GraphPriv {
         let mut range_points: Vec<HistoryEntry> = Vec::new();
         let points = self.points.borrow();
 
-        let min_point = points.iter().min_by_key(|entry| entry.timestamp);
-        let max_point = points.iter().max_by_key(|entry| entry.timestamp);
+        let min_timestamp = points.min_timestamp();
 
-        let min_limit = now - *self.max_duration_secs.borrow();
-        let min_limit = min_point.map_or(min_limit, |entry| min_limit.max(entry.timestamp));
-
-        let max_limit = now;
+        let max_limit = now.saturating_sub(self.view_offset_secs.get());
+        let min_limit = max_limit.saturating_sub(*self.max_duration_secs.borrow());
+        let min_limit = min_timestamp.map_or(min_limit, |ts| min_limit.max(ts));
 
         let mins = (max_limit - min_limit) / 60;
 
@@ -244,15 +427,48 @@ impl WidgetImpl for GraphPriv {
             }
             range_points.push(*entry);
         }
-        if let Some(max) = max_point {
-            let mut new_max = max.clone();
-            new_max.timestamp = now;
+        // extend the most recent in-range sample's line up to the right edge of the view,
+        // since samples only arrive every recorder interval rather than continuously
+        if let Some(mut new_max) = range_points.last().copied() {
+            new_max.timestamp = max_limit;
             range_points.push(new_max)
         }
+        let range_points_for_crosshair = range_points.clone();
+
+        let draw_health = self.draw_health.get();
+        let max_watts = if draw_health {
+            0.0
+        } else {
+            self.effective_max_watts(&range_points)
+        };
 
         // draw points
         if mins != 0 {
-            draw_points(&ctx, main_w, main_h, range_points, min_limit, mins);
+            if draw_health {
+                draw_points_health(&ctx, main_w, main_h, &range_points, min_limit, mins);
+            } else {
+                if max_watts > 0.0 {
+                    draw_points_secondary(
+                        &ctx,
+                        main_w,
+                        main_h,
+                        &range_points,
+                        min_limit,
+                        mins,
+                        max_watts,
+                    );
+                }
+                let low_battery_color = self.low_battery_color.borrow();
+                let charging_color = self.charging_color.borrow();
+                let normal_color = self.normal_color.borrow();
+                let colors = GraphColors {
+                    low_battery_threshold: self.low_battery_threshold.get(),
+                    low_battery: &low_battery_color,
+                    charging: &charging_color,
+                    normal: &normal_color,
+                };
+                draw_points(&ctx, main_w, main_h, range_points, min_limit, mins, &colors);
+            }
         }
 
         ctx.set_font_size(12.0);
@@ -265,13 +481,93 @@ impl WidgetImpl for GraphPriv {
             main_w,
             main_h,
             *self.draw_bars.borrow(),
+            &self.time_format.borrow(),
+            &self.date_format.borrow(),
         );
         draw_grid_horizontal(&ctx, main_w, main_h, *self.draw_bars.borrow());
+        if max_watts > 0.0 {
+            draw_grid_horizontal_secondary(&ctx, main_w, main_h, max_watts);
+        }
+
+        if mins != 0 {
+            if let Some(hover_x) = *self.hover_x.borrow() {
+                let offset_x = x + 1.0 + req_w * 0.05 + 20.0;
+                draw_crosshair(
+                    &ctx,
+                    hover_x - offset_x,
+                    main_w,
+                    main_h,
+                    &range_points_for_crosshair,
+                    min_limit,
+                    mins,
+                    draw_health,
+                    &self.time_format.borrow(),
+                );
+            } else if self.annotate_latest.get() {
+                draw_latest_label(
+                    &ctx,
+                    main_w,
+                    main_h,
+                    &range_points_for_crosshair,
+                    min_limit,
+                    mins,
+                    draw_health,
+                );
+            }
+        }
 
         drop(ctx);
     }
 }
 
+/// which of the configured colors a segment should be painted with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorClass {
+    Charging,
+    Low,
+    Normal,
+}
+
+/// charging takes priority over the low-battery threshold, e.g. a device plugged in below the
+/// threshold still reads as charging rather than low. `value` is on the `0..100` scale used by
+/// `HistoryEntry::value`, `low_battery_threshold` is the `0.0..=1.0` fraction it's compared against
+fn classify(state: State, value: f64, low_battery_threshold: f64) -> ColorClass {
+    if matches!(state, State::Charging | State::FullyCharged) {
+        ColorClass::Charging
+    } else if value / 100.0 < low_battery_threshold {
+        ColorClass::Low
+    } else {
+        ColorClass::Normal
+    }
+}
+
+/// the configured colors/threshold `draw_points` paints each segment with
+struct GraphColors<'a> {
+    low_battery_threshold: f64,
+    low_battery: &'a RGBA,
+    charging: &'a RGBA,
+    normal: &'a RGBA,
+}
+
+impl GraphColors<'_> {
+    fn for_class(&self, class: ColorClass) -> &RGBA {
+        match class {
+            ColorClass::Charging => self.charging,
+            ColorClass::Low => self.low_battery,
+            ColorClass::Normal => self.normal,
+        }
+    }
+}
+
+/// states for which the reported percentage is unreliable, e.g. right after plugging/unplugging
+/// the charger; the graph should leave a gap instead of plotting a trend through these points
+fn is_unknown_state(state: State) -> bool {
+    matches!(
+        state,
+        State::Unknown | State::Empty | State::PendingCharge | State::PendingDischarge
+    )
+}
+
 fn draw_points(
     ctx: &gtk::cairo::Context,
     main_w: f64,
@@ -279,16 +575,20 @@ fn draw_points(
     points: Vec<HistoryEntry>,
     min_limit: u32,
     mins: u32,
+    colors: &GraphColors,
 ) {
-    ctx.set_source_rgba(0.0, 0.9, 0.2, 1.0);
     ctx.move_to(0.0, main_h);
     let mut last_jump = 0.0;
     let mut last_x = 0.0;
     let mut last_perc = -1.0;
+    let mut segment_class: Option<ColorClass> = None;
+    let mut in_gap = false;
     for HistoryEntry {
         timestamp: instant,
         value: percentage,
-        state: _,
+        state,
+        power_draw: _,
+        health: _,
     } in points.into_iter()
     {
         let x = match instant.checked_sub(min_limit) {
@@ -303,24 +603,62 @@ fn draw_points(
         let x = x / mins as f64; // percentage
         let x = x * main_w;
         let y = main_h * (1.0 - percentage / 100.0);
-        if last_perc == -1.0 {
+        let class = classify(state, percentage, colors.low_battery_threshold);
+
+        if is_unknown_state(state) {
+            // close off whatever segment was open and leave a gap until the state recovers
+            if !in_gap {
+                if let Some(segment_class) = segment_class {
+                    ctx.line_to(last_x, main_h);
+                    ctx.line_to(last_jump, main_h);
+                    ctx.set_source_color(colors.for_class(segment_class));
+                    ctx.fill().unwrap();
+                }
+                in_gap = true;
+            }
             last_perc = percentage;
+            last_x = x;
+            continue;
+        }
+        if last_perc == -1.0 {
+            // first real point: anchor the fill's left edge to x=0
             last_jump = x;
             ctx.line_to(0.0, y);
+            segment_class = Some(class);
+        } else if in_gap {
+            // resuming after a gap: start a fresh path instead of connecting across it
+            last_jump = x;
+            ctx.move_to(x, y);
+            segment_class = Some(class);
         }
+        in_gap = false;
         // log::debug!("p: {}% y: {}", percentage, y);
 
         if percentage == 0.0 && (percentage - last_perc).abs() > 10.0 {
             ctx.line_to(last_x, main_h);
             ctx.line_to(last_jump, main_h);
+            ctx.set_source_color(colors.for_class(segment_class.unwrap_or(class)));
             ctx.fill().unwrap();
             ctx.move_to(x, y);
             last_jump = x;
+            segment_class = Some(class);
         } else if last_perc == 0.0 && (percentage - last_perc).abs() > 10.0 {
             ctx.move_to(last_x, main_h);
             ctx.line_to(x, y);
             // ctx.stroke().unwrap();
             last_jump = x;
+            segment_class = Some(class);
+        } else if segment_class.is_some_and(|c| c != class) {
+            // the color class changed mid-segment: close the previous segment's polygon
+            // and start a new one with the new class's color
+            ctx.line_to(x, main_h);
+            ctx.line_to(last_jump, main_h);
+            ctx.set_source_color(colors.for_class(segment_class.unwrap()));
+            ctx.fill().unwrap();
+            ctx.move_to(x, main_h);
+            ctx.line_to(x, y);
+            last_jump = x;
+            segment_class = Some(class);
         } else {
             // ctx.rectangle(x - 2.0, y, 4.0, main_h - y);
             // ctx.fill().unwrap();
@@ -330,9 +668,195 @@ fn draw_points(
         last_x = x;
     }
 
-    ctx.line_to(last_x, main_h);
-    ctx.line_to(last_jump, main_h);
+    if !in_gap {
+        ctx.line_to(last_x, main_h);
+        ctx.line_to(last_jump, main_h);
+        ctx.set_source_color(colors.for_class(segment_class.unwrap_or(ColorClass::Normal)));
+        ctx.fill().unwrap();
+    }
+}
+
+/// draws the instantaneous power draw as a plain line scaled against `max_watts`, without a
+/// fill, so it reads as an overlay on top of the percentage area
+fn draw_points_secondary(
+    ctx: &gtk::cairo::Context,
+    main_w: f64,
+    main_h: f64,
+    points: &[HistoryEntry],
+    min_limit: u32,
+    mins: u32,
+    max_watts: f64,
+) {
+    ctx.new_path();
+    let mut started = false;
+    for entry in points.iter() {
+        let Some(watts) = entry.power_draw else {
+            continue;
+        };
+        let x = match entry.timestamp.checked_sub(min_limit) {
+            Some(x) => x,
+            None => continue,
+        };
+        let x = x as f64 / 60.0 / mins as f64 * main_w;
+        let y = main_h * (1.0 - (watts / max_watts).clamp(0.0, 1.0));
+        if started {
+            ctx.line_to(x, y);
+        } else {
+            ctx.move_to(x, y);
+            started = true;
+        }
+    }
+    if started {
+        ctx.set_source_rgba(0.3, 0.6, 1.0, 0.9);
+        ctx.set_line_width(1.5);
+        ctx.stroke().unwrap();
+    }
+}
+
+/// draws the long-term battery health (capacity degradation) trend, on the same 0-100% scale
+/// as `draw_grid_horizontal`, as a plain line with no charge-state coloring
+fn draw_points_health(
+    ctx: &gtk::cairo::Context,
+    main_w: f64,
+    main_h: f64,
+    points: &[HistoryEntry],
+    min_limit: u32,
+    mins: u32,
+) {
+    ctx.new_path();
+    let mut started = false;
+    for entry in points.iter() {
+        let Some(health) = entry.health else {
+            continue;
+        };
+        let x = match entry.timestamp.checked_sub(min_limit) {
+            Some(x) => x,
+            None => continue,
+        };
+        let x = x as f64 / 60.0 / mins as f64 * main_w;
+        let y = main_h * (1.0 - (health / 100.0).clamp(0.0, 1.0));
+        if started {
+            ctx.line_to(x, y);
+        } else {
+            ctx.move_to(x, y);
+            started = true;
+        }
+    }
+    if started {
+        ctx.set_source_rgba(0.8, 0.2, 0.8, 1.0);
+        ctx.set_line_width(1.5);
+        ctx.stroke().unwrap();
+    }
+}
+
+/// linearly interpolates the percentage (or health, when `draw_health`) at `timestamp` from the
+/// two bracketing points in `points`, falling back to the nearest point if `timestamp` falls
+/// outside the range they cover
+fn interpolate_value(points: &[HistoryEntry], timestamp: u32, draw_health: bool) -> Option<f64> {
+    let value_at = |entry: &HistoryEntry| {
+        if draw_health {
+            entry.health
+        } else {
+            Some(entry.value)
+        }
+    };
+    let mut prev: Option<&HistoryEntry> = None;
+    for entry in points {
+        if value_at(entry).is_none() {
+            continue;
+        }
+        if entry.timestamp >= timestamp {
+            return match prev {
+                Some(prev) if entry.timestamp != prev.timestamp => {
+                    let t = (timestamp - prev.timestamp) as f64
+                        / (entry.timestamp - prev.timestamp) as f64;
+                    Some(
+                        value_at(prev).unwrap()
+                            + (value_at(entry).unwrap() - value_at(prev).unwrap()) * t,
+                    )
+                }
+                _ => value_at(entry),
+            };
+        }
+        prev = Some(entry);
+    }
+    prev.and_then(value_at)
+}
+
+/// draws a vertical line at the hovered x plus a label with the interpolated value and
+/// timestamp at that point, reusing the text-measurement approach from `draw_grid_vertical`
+#[allow(clippy::too_many_arguments)]
+fn draw_crosshair(
+    ctx: &gtk::cairo::Context,
+    hover_x: f64,
+    main_w: f64,
+    main_h: f64,
+    points: &[HistoryEntry],
+    min_limit: u32,
+    mins: u32,
+    draw_health: bool,
+    time_format: &str,
+) {
+    if hover_x < 0.0 || hover_x > main_w {
+        return;
+    }
+    let timestamp = min_limit + (hover_x / main_w * mins as f64 * 60.0) as u32;
+    let Some(value) = interpolate_value(points, timestamp, draw_health) else {
+        return;
+    };
+
+    ctx.set_source_color(&RGBA::WHITE.with_alpha(0.6));
+    ctx.move_to(hover_x, 0.0);
+    ctx.line_to(hover_x, main_h);
+    ctx.stroke().unwrap();
+
+    let date_time: DateTime<Local> = chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .unwrap()
+        .into();
+    let label = format!("{:.0}%  {}", value, date_time.format(time_format));
+
+    ctx.set_source_color(&RGBA::WHITE);
+    let ext = ctx.text_extents(&label).unwrap();
+    let label_x = (hover_x - ext.width() / 2.0).clamp(0.0, (main_w - ext.width()).max(0.0));
+    ctx.move_to(label_x, 12.0);
+    ctx.show_text(&label).unwrap();
+}
+
+/// draws a small marker and label at the most recent sample with a value, analogous to
+/// `draw_crosshair` but anchored to the latest point instead of the pointer position; used when
+/// the pointer isn't hovering the graph so the current value stays visible at a glance
+fn draw_latest_label(
+    ctx: &gtk::cairo::Context,
+    main_w: f64,
+    main_h: f64,
+    points: &[HistoryEntry],
+    min_limit: u32,
+    mins: u32,
+    draw_health: bool,
+) {
+    let value_at =
+        |entry: &HistoryEntry| if draw_health { entry.health } else { Some(entry.value) };
+    let Some(latest) = points.iter().rev().find(|entry| value_at(entry).is_some()) else {
+        return;
+    };
+    let value = value_at(latest).unwrap();
+    let x = match latest.timestamp.checked_sub(min_limit) {
+        Some(x) => x,
+        None => return,
+    };
+    let x = (x as f64 / 60.0 / mins as f64 * main_w).clamp(0.0, main_w);
+    let y = main_h * (1.0 - (value / 100.0).clamp(0.0, 1.0));
+
+    ctx.set_source_color(&RGBA::WHITE);
+    ctx.arc(x, y, 2.5, 0.0, std::f64::consts::TAU);
     ctx.fill().unwrap();
+
+    let label = format!("{:.0}%", value);
+    let ext = ctx.text_extents(&label).unwrap();
+    let label_x = (x - ext.width() / 2.0).clamp(0.0, (main_w - ext.width()).max(0.0));
+    let label_y = (y - 6.0).max(ext.height());
+    ctx.move_to(label_x, label_y);
+    ctx.show_text(&label).unwrap();
 }
 
 fn draw_grid_vertical(
@@ -344,6 +868,8 @@ fn draw_grid_vertical(
     main_w: f64,
     main_h: f64,
     draw_bars: bool,
+    time_format: &str,
+    date_format: &str,
 ) {
     let grid_v = (min_instant..max_limit)
         .step_by((step * 60) as usize)
@@ -373,17 +899,12 @@ fn draw_grid_vertical(
             .unwrap()
             .into();
         let mut y_offset = 5.0;
-        // TODO allow for date and time format customization
-        let formatted_date = if step >= 720 {
-            Some(date_time.format("%d/%m").to_string())
-        } else {
-            None
-        };
-        let formatted_time = if step != 1440 {
-            Some(date_time.format("%H:%M").to_string())
-        } else {
-            None
-        };
+        // when the caller customizes a format away from the default, show it uniformly
+        // instead of gating it by `step`, so a combined date+time format isn't hidden
+        let show_date = date_format != "%d/%m" || step >= 720;
+        let show_time = time_format != "%H:%M" || step != 1440;
+        let formatted_date = show_date.then(|| date_time.format(date_format).to_string());
+        let formatted_time = show_time.then(|| date_time.format(time_format).to_string());
 
         ctx.set_source_color(&RGBA::WHITE);
         if let Some(formatted) = formatted_time {
@@ -427,7 +948,48 @@ fn draw_grid_horizontal(ctx: &gtk::cairo::Context, main_w: f64, main_h: f64, dra
     }
 }
 
-impl GraphPriv {}
+/// right-aligned axis labels for the watts overlay, analogous to `draw_grid_horizontal`
+fn draw_grid_horizontal_secondary(
+    ctx: &gtk::cairo::Context,
+    main_w: f64,
+    main_h: f64,
+    max_watts: f64,
+) {
+    let grid_h = (0..=4).collect::<Vec<_>>();
+
+    for step in grid_h.iter() {
+        let ratio = *step as f64 / 4.0;
+        let y = main_h * (1.0 - ratio);
+        let watts = format!(" {:.1}W", max_watts * ratio);
+
+        ctx.set_source_color(&RGBA::from_str("#4d99ff").unwrap());
+        let ext = ctx.text_extents(&watts).unwrap();
+        ctx.move_to(main_w, y + ext.height() / 2.0);
+        ctx.show_text(&watts).unwrap();
+    }
+}
+
+impl GraphPriv {
+    /// ceiling for the watts overlay: the configured `max-watts` if set, otherwise the max
+    /// power draw among `points` (ratcheted upward across calls when `keep-max-watts` is set)
+    fn effective_max_watts(&self, points: &[HistoryEntry]) -> f64 {
+        let configured = self.max_watts.get();
+        if configured > 0.0 {
+            return configured;
+        }
+        let observed = points
+            .iter()
+            .filter_map(|entry| entry.power_draw)
+            .fold(0.0_f64, f64::max);
+        if self.keep_max_watts.get() {
+            let max = self.auto_max_watts.get().max(observed);
+            self.auto_max_watts.set(max);
+            max
+        } else {
+            observed
+        }
+    }
+}
 
 #[allow(clippy::new_without_default)]
 impl Graph {
@@ -437,7 +999,20 @@ impl Graph {
     }
     /// This assumes the points are sorted by increasing time
     pub fn set_points(&self, points: &Vec<HistoryEntry>) {
-        self.imp().points.replace(points.to_vec());
+        let window_secs = self.max_duration_secs();
+        let mut ring = self.imp().points.borrow_mut();
+        ring.clear();
+        for entry in points {
+            ring.push(*entry, window_secs);
+        }
+        drop(ring);
+        self.queue_draw();
+    }
+
+    /// appends a single point, evicting anything older than `max-duration-secs`
+    pub fn push_point(&self, entry: HistoryEntry) {
+        let window_secs = self.max_duration_secs();
+        self.imp().points.borrow_mut().push(entry, window_secs);
         self.queue_draw();
     }
 }
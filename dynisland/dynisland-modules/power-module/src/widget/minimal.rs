@@ -1,10 +1,11 @@
 use std::cell::RefCell;
 
 use dynisland_core::{
-    abi::{glib, gtk},
+    abi::{gdk, glib, gtk, log},
     cast_dyn_any,
     dynamic_activity::DynamicActivity,
 };
+use gdk::RGBA;
 use glib::{
     prelude::ObjectExt,
     subclass::{
@@ -43,7 +44,14 @@ pub struct MinimalPriv {
     pub charging_color: RefCell<String>,
     #[property(get, set)]
     pub normal_color: RefCell<String>,
-    //TODO add low battery treshold
+    /// fraction (`0.0..=1.0`) below which the battery is considered low when `color_stops` is empty
+    #[property(get, set)]
+    pub low_battery_threshold: RefCell<f64>,
+    /// `"pos:color,pos:color,..."` gradient stops the fill is interpolated across as it drains,
+    /// e.g. `"0:red,0.5:orange,1:green"`; falls back to a binary `low_battery_color`/`normal_color`
+    /// switch at `low_battery_threshold` when empty
+    #[property(get, set, type=String)]
+    pub color_stops: RefCell<Vec<(f64, RGBA)>>,
 }
 
 impl Default for MinimalPriv {
@@ -53,8 +61,72 @@ impl Default for MinimalPriv {
             low_battery_color: RefCell::new("red".to_string()),
             charging_color: RefCell::new("green".to_string()),
             normal_color: RefCell::new("white".to_string()),
+            low_battery_threshold: RefCell::new(0.2),
+            color_stops: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+/// linearly interpolates each RGBA channel independently, `t` clamped to `0.0..=1.0`
+pub(super) fn lerp_color(a: &RGBA, b: &RGBA, t: f64) -> RGBA {
+    let t = t.clamp(0.0, 1.0) as f32;
+    RGBA::new(
+        a.red() + (b.red() - a.red()) * t,
+        a.green() + (b.green() - a.green()) * t,
+        a.blue() + (b.blue() - a.blue()) * t,
+        a.alpha() + (b.alpha() - a.alpha()) * t,
+    )
+}
+
+/// finds the stops bracketing `percentage` and interpolates between them, `None` if `stops` is empty
+pub(super) fn color_for_stops(stops: &[(f64, RGBA)], percentage: f64) -> Option<RGBA> {
+    let mut sorted = stops.to_vec();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let (first, last) = (sorted.first()?, sorted.last().unwrap());
+    if sorted.len() == 1 || percentage <= first.0 {
+        return Some(first.1);
+    }
+    if percentage >= last.0 {
+        return Some(last.1);
+    }
+    for pair in sorted.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if percentage <= b.0 {
+            let t = (percentage - a.0) / (b.0 - a.0);
+            return Some(lerp_color(&a.1, &b.1, t));
         }
     }
+    Some(last.1)
+}
+
+pub(super) fn parse_color_stops(value: &str) -> Option<Vec<(f64, RGBA)>> {
+    // commas inside `rgb(...)`/`rgba(...)` belong to the color, not to the list of stops
+    let mut depth = 0_i32;
+    value
+        .split(move |c: char| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            c == ',' && depth == 0
+        })
+        .filter(|s| !s.trim().is_empty())
+        .map(|stop| {
+            let (pos, color) = stop.split_once(':')?;
+            let pos: f64 = pos.trim().parse().ok()?;
+            let color = RGBA::parse(color.trim()).ok()?;
+            Some((pos, color))
+        })
+        .collect()
+}
+
+pub(super) fn format_color_stops(stops: &[(f64, RGBA)]) -> String {
+    stops
+        .iter()
+        .map(|(pos, color)| format!("{pos}:{color}"))
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 #[glib::object_subclass]
@@ -90,6 +162,48 @@ impl ObjectImpl for MinimalPriv {
         }
         self.dispose_template();
     }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "low-battery-color" => {
+                self.low_battery_color.replace(value.get().unwrap());
+            }
+            "charging-color" => {
+                self.charging_color.replace(value.get().unwrap());
+            }
+            "normal-color" => {
+                self.normal_color.replace(value.get().unwrap());
+            }
+            "low-battery-threshold" => {
+                self.low_battery_threshold.replace(value.get().unwrap());
+            }
+            "color-stops" => {
+                let name: String = value.get().unwrap();
+                if let Some(stops) = parse_color_stops(&name) {
+                    self.color_stops.replace(stops);
+                } else {
+                    log::warn!("invalid color stops: {name}");
+                }
+            }
+            _ => {
+                log::warn!("Minimal: invalid property received: {}", pspec.name());
+            }
+        }
+    }
+
+    fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "low-battery-color" => self.low_battery_color.borrow().to_value(),
+            "charging-color" => self.charging_color.borrow().to_value(),
+            "normal-color" => self.normal_color.borrow().to_value(),
+            "low-battery-threshold" => self.low_battery_threshold.borrow().to_value(),
+            "color-stops" => format_color_stops(&self.color_stops.borrow()).to_value(),
+            _ => {
+                log::warn!("Minimal: invalid property received: {}", pspec.name());
+                glib::Value::from_type(glib::Type::UNIT)
+            }
+        }
+    }
 }
 
 impl WidgetImpl for MinimalPriv {}
@@ -111,17 +225,7 @@ impl Minimal {
                 let perc = *cast_dyn_any!(new_value, f64).unwrap();
                 minimal.imp().battery.set_percentage(perc);
                 if !minimal.imp().battery.charging() {
-                    if minimal.imp().battery.percentage() < 0.2 {
-                        minimal
-                            .imp()
-                            .battery
-                            .set_fill_color(minimal.imp().low_battery_color.borrow().clone());
-                    } else {
-                        minimal
-                            .imp()
-                            .battery
-                            .set_fill_color(minimal.imp().normal_color.borrow().clone());
-                    }
+                    minimal.update_fill_color();
                 } else {
                     minimal
                         .imp()
@@ -144,20 +248,110 @@ impl Minimal {
                         .battery
                         .set_fill_color(minimal.imp().charging_color.borrow().clone());
                 } else {
-                    if minimal.imp().battery.percentage() < 0.2 {
-                        minimal
-                            .imp()
-                            .battery
-                            .set_fill_color(minimal.imp().low_battery_color.borrow().clone());
-                    } else {
-                        minimal
-                            .imp()
-                            .battery
-                            .set_fill_color(minimal.imp().normal_color.borrow().clone());
-                    }
+                    minimal.update_fill_color();
                 }
             })
             .unwrap();
         this
     }
+
+    /// colors the battery fill from `color_stops` if any are set, otherwise falls back to a
+    /// binary switch between `low_battery_color` and `normal_color` at `low_battery_threshold`
+    fn update_fill_color(&self) {
+        let percentage = self.imp().battery.percentage();
+        let stops = self.imp().color_stops.borrow();
+        let color = if let Some(color) = color_for_stops(&stops, percentage) {
+            color.to_string()
+        } else if percentage < *self.imp().low_battery_threshold.borrow() {
+            self.imp().low_battery_color.borrow().clone()
+        } else {
+            self.imp().normal_color.borrow().clone()
+        };
+        self.imp().battery.set_fill_color(color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color_stops_parses_position_color_pairs() {
+        let stops = parse_color_stops("0:red,0.5:#ffaa00,1:green").unwrap();
+        assert_eq!(stops.len(), 3);
+        assert_eq!(stops[0], (0.0, RGBA::parse("red").unwrap()));
+        assert_eq!(stops[1], (0.5, RGBA::parse("#ffaa00").unwrap()));
+        assert_eq!(stops[2], (1.0, RGBA::parse("green").unwrap()));
+    }
+
+    #[test]
+    fn parse_color_stops_ignores_blank_entries() {
+        // a trailing comma (or stray whitespace-only segment) shouldn't turn a single stop
+        // into a parse failure
+        let stops = parse_color_stops("0:red,").unwrap();
+        assert_eq!(stops, vec![(0.0, RGBA::parse("red").unwrap())]);
+    }
+
+    #[test]
+    fn parse_color_stops_rejects_malformed_tokens() {
+        assert_eq!(parse_color_stops("not-a-stop"), None);
+        assert_eq!(parse_color_stops("0:not-a-color"), None);
+        assert_eq!(parse_color_stops("not-a-number:red"), None);
+    }
+
+    #[test]
+    fn parse_color_stops_empty_string_is_no_stops() {
+        assert_eq!(parse_color_stops(""), Some(Vec::new()));
+    }
+
+    #[test]
+    fn format_color_stops_round_trips_through_parse() {
+        let stops = vec![(0.0, RGBA::parse("red").unwrap()), (1.0, RGBA::parse("green").unwrap())];
+        let formatted = format_color_stops(&stops);
+        assert_eq!(parse_color_stops(&formatted).unwrap(), stops);
+    }
+
+    #[test]
+    fn color_for_stops_empty_is_none() {
+        assert_eq!(color_for_stops(&[], 0.5), None);
+    }
+
+    #[test]
+    fn color_for_stops_single_stop_always_wins() {
+        // with only one stop there's nothing to interpolate between, so every percentage
+        // (even ones clearly outside the stop's own position) resolves to that stop's color
+        let red = RGBA::parse("red").unwrap();
+        let stops = vec![(0.5, red)];
+        assert_eq!(color_for_stops(&stops, 0.0), Some(red));
+        assert_eq!(color_for_stops(&stops, 0.5), Some(red));
+        assert_eq!(color_for_stops(&stops, 1.0), Some(red));
+    }
+
+    #[test]
+    fn color_for_stops_clamps_outside_the_stop_range() {
+        let (red, green) = (RGBA::parse("red").unwrap(), RGBA::parse("green").unwrap());
+        let stops = vec![(0.2, red), (0.8, green)];
+        assert_eq!(color_for_stops(&stops, 0.0), Some(red));
+        assert_eq!(color_for_stops(&stops, 1.0), Some(green));
+    }
+
+    #[test]
+    fn color_for_stops_interpolates_between_bracketing_stops() {
+        let (red, green) = (RGBA::parse("red").unwrap(), RGBA::parse("green").unwrap());
+        let stops = vec![(0.0, red), (1.0, green)];
+        let mid = color_for_stops(&stops, 0.5).unwrap();
+        assert_eq!(mid, lerp_color(&red, &green, 0.5));
+    }
+
+    #[test]
+    fn color_for_stops_handles_unsorted_input() {
+        // stops are passed in the order the user wrote them, not necessarily sorted by position
+        let (red, green) = (RGBA::parse("red").unwrap(), RGBA::parse("green").unwrap());
+        let unsorted = vec![(1.0, green), (0.0, red)];
+        let sorted = vec![(0.0, red), (1.0, green)];
+        assert_eq!(
+            color_for_stops(&unsorted, 0.5),
+            color_for_stops(&sorted, 0.5)
+        );
+    }
 }
@@ -0,0 +1,219 @@
+use std::cell::{Cell, RefCell};
+
+use dynisland_core::abi::{gdk, glib, gtk, log};
+use gdk::RGBA;
+use glib::{
+    subclass::{
+        object::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
+        types::{ObjectSubclass, ObjectSubclassExt},
+    },
+    Object, Properties,
+};
+use gtk::{
+    graphene::Rect,
+    prelude::*,
+    subclass::widget::{WidgetClassExt, WidgetImpl},
+    BinLayout,
+};
+
+glib::wrapper! {
+    pub struct Sparkline(ObjectSubclass<SparklinePriv>)
+    @extends gtk::Widget;
+}
+#[derive(Properties)]
+#[properties(wrapper_type = Sparkline)]
+pub struct SparklinePriv {
+    #[property(get, set)]
+    capacity: Cell<u32>,
+    /// Ceiling used to scale samples to the widget height, e.g. `1.0` for a 0..1 ratio
+    #[property(get, set)]
+    max: Cell<f64>,
+    /// If true, the auto-detected max only ever grows, so the graph never rescales downward
+    #[property(get, set)]
+    keep_max: Cell<bool>,
+    #[property(get, set, type=String)]
+    fill_color: RefCell<RGBA>,
+
+    samples: RefCell<Vec<f64>>,
+    head: Cell<usize>,
+    auto_max: Cell<f64>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for SparklinePriv {
+    const NAME: &'static str = "BatterySparklineWidget";
+    type Type = Sparkline;
+    type ParentType = gtk::Widget;
+
+    fn class_init(klass: &mut Self::Class) {
+        klass.set_layout_manager_type::<BinLayout>();
+        klass.set_css_name("battery-sparkline-widget");
+    }
+}
+#[allow(clippy::derivable_impls)]
+impl Default for SparklinePriv {
+    fn default() -> Self {
+        Self {
+            capacity: Cell::new(60),
+            max: Cell::new(1.0),
+            keep_max: Cell::new(false),
+            fill_color: RefCell::new(RGBA::WHITE),
+            samples: RefCell::new(Vec::new()),
+            head: Cell::new(0),
+            auto_max: Cell::new(1.0),
+        }
+    }
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for SparklinePriv {
+    fn constructed(&self) {
+        self.parent_constructed();
+    }
+
+    fn dispose(&self) {
+        while let Some(child) = self.obj().first_child() {
+            child.unparent();
+        }
+    }
+
+    fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+        match pspec.name() {
+            "capacity" => {
+                let capacity: u32 = value.get().unwrap();
+                self.capacity.set(capacity.max(1));
+                // a capacity change invalidates the ring's chronological ordering, start over
+                self.samples.borrow_mut().clear();
+                self.head.set(0);
+                self.obj().queue_draw();
+            }
+            "max" => {
+                self.max.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "keep-max" => {
+                self.keep_max.set(value.get().unwrap());
+                self.obj().queue_draw();
+            }
+            "fill-color" => {
+                let name: String = value.get().unwrap();
+                if let Ok(color) = RGBA::parse(&name) {
+                    self.fill_color.replace(color);
+                    self.obj().queue_draw();
+                } else {
+                    log::warn!("invalid fill color: {name}");
+                }
+            }
+            _ => {
+                log::warn!("Sparkline: invalid property received: {}", pspec.name());
+            }
+        }
+    }
+
+    fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+        match pspec.name() {
+            "capacity" => self.capacity.get().to_value(),
+            "max" => self.max.get().to_value(),
+            "keep-max" => self.keep_max.get().to_value(),
+            "fill-color" => self.fill_color.borrow().to_string().to_value(),
+            _ => self.derived_property(id, pspec),
+        }
+    }
+}
+
+impl WidgetImpl for SparklinePriv {
+    fn snapshot(&self, snapshot: &gtk::Snapshot) {
+        let (w, h) = (self.obj().width() as f64, self.obj().height() as f64);
+        let rect = Rect::new(0.0, 0.0, w as f32, h as f32);
+        let ctx = snapshot.append_cairo(&rect);
+
+        let samples = self.samples.borrow();
+        let capacity = self.capacity.get().max(1) as usize;
+        if samples.len() < 2 {
+            drop(ctx);
+            return;
+        }
+        let max = self.effective_max(&samples);
+
+        let fill_color = self.fill_color.borrow();
+        ctx.set_line_width(1.5);
+        ctx.set_source_color(&fill_color);
+
+        // chronological order: oldest sample first, `head` is the index of the oldest slot once
+        // the ring has wrapped at least once
+        let head = if samples.len() == capacity {
+            self.head.get()
+        } else {
+            0
+        };
+        let to_point = |i: usize, value: f64| {
+            let x = i as f64 / (capacity - 1).max(1) as f64 * w;
+            let y = h * (1.0 - (value / max).clamp(0.0, 1.0));
+            (x, y)
+        };
+
+        ctx.move_to(0.0, h);
+        for i in 0..samples.len() {
+            let (x, y) = to_point(i, samples[(head + i) % samples.len()]);
+            ctx.line_to(x, y);
+        }
+        let (last_x, _) = to_point(samples.len() - 1, 0.0);
+        ctx.line_to(last_x, h);
+        ctx.close_path();
+        ctx.set_source_color(&fill_color.with_alpha(fill_color.alpha() * 0.3));
+        ctx.fill_preserve().unwrap();
+
+        ctx.new_path();
+        for i in 0..samples.len() {
+            let (x, y) = to_point(i, samples[(head + i) % samples.len()]);
+            if i == 0 {
+                ctx.move_to(x, y);
+            } else {
+                ctx.line_to(x, y);
+            }
+        }
+        ctx.set_source_color(&fill_color);
+        ctx.stroke().unwrap();
+
+        drop(ctx);
+    }
+}
+
+impl SparklinePriv {
+    fn effective_max(&self, samples: &[f64]) -> f64 {
+        let configured = self.max.get();
+        if self.keep_max.get() {
+            self.auto_max.get().max(configured)
+        } else {
+            samples.iter().cloned().fold(configured, f64::max)
+        }
+    }
+}
+
+#[allow(clippy::new_without_default)]
+impl Sparkline {
+    pub fn new() -> Self {
+        let this: Self = Object::builder().build();
+        this
+    }
+
+    /// Appends `value` until `capacity` samples are held, after that overwrites the oldest slot
+    /// with it and advances the head; queues a redraw either way.
+    pub fn push(&self, value: f64) {
+        let capacity = self.imp().capacity.get().max(1) as usize;
+        let mut samples = self.imp().samples.borrow_mut();
+        if samples.len() < capacity {
+            samples.push(value);
+        } else {
+            let head = self.imp().head.get();
+            samples[head] = value;
+            self.imp().head.set((head + 1) % capacity);
+        }
+        drop(samples);
+
+        if self.imp().keep_max.get() && value > self.imp().auto_max.get() {
+            self.imp().auto_max.set(value);
+        }
+        self.queue_draw();
+    }
+}
@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+
+use dynisland_core::{
+    abi::{glib, gtk},
+    cast_dyn_any,
+    dynamic_activity::DynamicActivity,
+};
+use glib::{
+    prelude::ObjectExt,
+    subclass::{
+        object::{DerivedObjectProperties, ObjectImpl, ObjectImplExt},
+        types::{ObjectSubclass, ObjectSubclassExt, ObjectSubclassIsExt},
+        InitializingObject,
+    },
+    types::StaticTypeExt,
+    Object, Properties,
+};
+use gtk::{
+    prelude::WidgetExt,
+    subclass::widget::{
+        CompositeTemplateClass, CompositeTemplateDisposeExt, CompositeTemplateInitializingExt,
+        WidgetClassExt, WidgetImpl,
+    },
+    BinLayout, CompositeTemplate, TemplateChild,
+};
+
+use super::sparkline::Sparkline;
+
+glib::wrapper! {
+    pub struct Trend(ObjectSubclass<TrendPriv>)
+    @extends gtk::Widget;
+}
+
+#[derive(CompositeTemplate, Properties)]
+#[properties(wrapper_type = Trend)]
+#[template(resource = "/com/github/cr3eperall/dynislandModules/powerModule/trend.ui")]
+pub struct TrendPriv {
+    #[template_child]
+    pub sparkline: TemplateChild<Sparkline>,
+    #[property(get, set)]
+    pub normal_color: RefCell<String>,
+}
+
+impl Default for TrendPriv {
+    fn default() -> Self {
+        Self {
+            sparkline: TemplateChild::default(),
+            normal_color: RefCell::new("white".to_string()),
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for TrendPriv {
+    const NAME: &'static str = "PowerTrendWidget";
+    type Type = Trend;
+    type ParentType = gtk::Widget;
+
+    fn class_init(klass: &mut Self::Class) {
+        // if you use custom widgets from core you need to ensure the type
+        Sparkline::ensure_type();
+        klass.set_layout_manager_type::<BinLayout>();
+        klass.bind_template();
+        // Warning: template callbacks only work if the module is embedded
+        // so don't call `klass.bind_template_instance_callbacks();` or dynisland will crash
+        // manually connect signals in `ObjectImpl::constructed` instead
+    }
+
+    fn instance_init(obj: &InitializingObject<Self>) {
+        obj.init_template();
+    }
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for TrendPriv {
+    fn constructed(&self) {
+        self.parent_constructed();
+    }
+
+    fn dispose(&self) {
+        while let Some(child) = self.obj().first_child() {
+            child.unparent();
+        }
+        self.dispose_template();
+    }
+}
+
+impl WidgetImpl for TrendPriv {}
+
+impl Trend {
+    /// registered properties:
+    /// * `percentage`: `f64`
+    pub fn new(activity: &mut DynamicActivity) -> Self {
+        let this: Self = Object::builder().build();
+        this.imp()
+            .sparkline
+            .set_fill_color(this.imp().normal_color.borrow().clone());
+
+        // register the property if it doesn't exist
+        // this way we can update multiple widgets with the same property
+        let _ = activity.add_dynamic_property("percentage", 0.0_f64);
+
+        let trend = this.clone();
+        activity
+            .subscribe_to_property("percentage", move |new_value| {
+                let perc = *cast_dyn_any!(new_value, f64).unwrap();
+                trend.imp().sparkline.push(perc);
+            })
+            .unwrap();
+        this
+    }
+}